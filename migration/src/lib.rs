@@ -3,12 +3,44 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20240301_000001_create_table;
+mod m20240315_000001_create_username_verification;
+mod m20240320_000001_add_voter_created_at;
+mod m20240325_000001_add_voter_status;
+mod m20240401_000001_create_voter_identity;
+mod m20240405_000001_create_crush_match;
+mod m20240410_000001_add_timestamps;
+mod m20240415_000001_create_season;
+mod m20240420_000001_hash_voter_addresses;
+mod m20240425_000001_create_rate_limit_bucket;
+mod m20240430_000001_add_voter_ip_hash;
+mod m20240505_000001_create_blocked_username;
+mod m20240510_000001_add_user_profile_settings;
+mod m20240515_000001_create_report;
+mod m20240520_000001_create_vote_flag;
+mod m20240525_000001_add_voter_shadow;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20240301_000001_create_table::Migration)]
+        vec![
+            Box::new(m20240301_000001_create_table::Migration),
+            Box::new(m20240315_000001_create_username_verification::Migration),
+            Box::new(m20240320_000001_add_voter_created_at::Migration),
+            Box::new(m20240325_000001_add_voter_status::Migration),
+            Box::new(m20240401_000001_create_voter_identity::Migration),
+            Box::new(m20240405_000001_create_crush_match::Migration),
+            Box::new(m20240410_000001_add_timestamps::Migration),
+            Box::new(m20240415_000001_create_season::Migration),
+            Box::new(m20240420_000001_hash_voter_addresses::Migration),
+            Box::new(m20240425_000001_create_rate_limit_bucket::Migration),
+            Box::new(m20240430_000001_add_voter_ip_hash::Migration),
+            Box::new(m20240505_000001_create_blocked_username::Migration),
+            Box::new(m20240510_000001_add_user_profile_settings::Migration),
+            Box::new(m20240515_000001_create_report::Migration),
+            Box::new(m20240520_000001_create_vote_flag::Migration),
+            Box::new(m20240525_000001_add_voter_shadow::Migration),
+        ]
     }
 }
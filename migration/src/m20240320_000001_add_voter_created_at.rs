@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .add_column(
+                        ColumnDef::new(Voter::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            // when existing votes were cast is unknown, the
+                            // epoch keeps them out of every time window but
+                            // purges by date still catch them. New rows get
+                            // their timestamp from the model.
+                            .default(Expr::val("1970-01-01 00:00:00+00:00")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // purges and windowed leaderboards filter on it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_created_at")
+                    .table(Voter::Table)
+                    .col(Voter::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_voter_created_at")
+                    .table(Voter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .drop_column(Voter::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    CreatedAt,
+}
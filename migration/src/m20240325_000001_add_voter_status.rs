@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // votes cast before background verification existed were verified inline
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .add_column(
+                        ColumnDef::new(Voter::Status)
+                            .string()
+                            .not_null()
+                            .default("confirmed"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .drop_column(Voter::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    Status,
+}
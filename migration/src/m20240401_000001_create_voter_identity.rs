@@ -0,0 +1,109 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoterIdentity::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VoterIdentity::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(VoterIdentity::Username)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(VoterIdentity::Code).string())
+                    .col(ColumnDef::new(VoterIdentity::PendingTokenHash).string())
+                    .col(ColumnDef::new(VoterIdentity::ClaimedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(VoterIdentity::TokenHash).string())
+                    .col(ColumnDef::new(VoterIdentity::VerifiedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(VoterIdentity::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // no foreign key, SQLite can't add one to an existing table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .add_column(ColumnDef::new(Voter::IdentityId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_identity_id")
+                    .table(Voter::Table)
+                    .col(Voter::IdentityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_voter_identity_id")
+                    .table(Voter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .drop_column(Voter::IdentityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(VoterIdentity::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoterIdentity {
+    Table,
+    Id,
+    Username,
+    Code,
+    PendingTokenHash,
+    ClaimedAt,
+    TokenHash,
+    VerifiedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    IdentityId,
+}
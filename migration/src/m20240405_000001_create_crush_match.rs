@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CrushMatch::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CrushMatch::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CrushMatch::IdentityAId).integer().not_null())
+                    .col(ColumnDef::new(CrushMatch::IdentityBId).integer().not_null())
+                    .col(
+                        ColumnDef::new(CrushMatch::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_crush_match_identity_a_id")
+                            .from_tbl(CrushMatch::Table)
+                            .from_col(CrushMatch::IdentityAId)
+                            .to_tbl(VoterIdentity::Table)
+                            .to_col(VoterIdentity::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_crush_match_identity_b_id")
+                            .from_tbl(CrushMatch::Table)
+                            .from_col(CrushMatch::IdentityBId)
+                            .to_tbl(VoterIdentity::Table)
+                            .to_col(VoterIdentity::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // created apart from the table so the seasons can replace it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crush_match_identities")
+                    .table(CrushMatch::Table)
+                    .col(CrushMatch::IdentityAId)
+                    .col(CrushMatch::IdentityBId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CrushMatch::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum CrushMatch {
+    Table,
+    Id,
+    IdentityAId,
+    IdentityBId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum VoterIdentity {
+    Table,
+    Id,
+}
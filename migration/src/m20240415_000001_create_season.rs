@@ -0,0 +1,247 @@
+use sea_orm_migration::{prelude::*, sea_orm::DatabaseBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Season::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Season::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Season::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Season::StartsAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Season::EndsAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Season::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the votes cast so far make up the first season, it stays open until
+        // the next one starts
+        let starts_at: SimpleExpr = match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => Expr::val("1970-01-01 00:00:00+00:00").into(),
+            _ => Expr::current_timestamp().into(),
+        };
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Season::Table)
+                    .columns([Season::Name, Season::StartsAt])
+                    .values_panic([Expr::val("Season 1").into(), starts_at])
+                    .to_owned(),
+            )
+            .await?;
+
+        // no foreign key, SQLite can't add one to an existing table
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .add_column(ColumnDef::new(Voter::SeasonId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Voter::Table)
+                    .value(
+                        Voter::SeasonId,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(
+                                Query::select()
+                                    .expr(Expr::col(Season::Id).min())
+                                    .from(Season::Table)
+                                    .to_owned()
+                                    .into_sub_query_statement(),
+                            ),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_season_id")
+                    .table(Voter::Table)
+                    .col(Voter::SeasonId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // the same voters can match again in every season
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrushMatch::Table)
+                    .add_column(ColumnDef::new(CrushMatch::SeasonId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(CrushMatch::Table)
+                    .value(
+                        CrushMatch::SeasonId,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(
+                                Query::select()
+                                    .expr(Expr::col(Season::Id).min())
+                                    .from(Season::Table)
+                                    .to_owned()
+                                    .into_sub_query_statement(),
+                            ),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_crush_match_identities")
+                    .table(CrushMatch::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crush_match_season_identities")
+                    .table(CrushMatch::Table)
+                    .col(CrushMatch::SeasonId)
+                    .col(CrushMatch::IdentityAId)
+                    .col(CrushMatch::IdentityBId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_crush_match_season_identities")
+                    .table(CrushMatch::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // only the matches of the first season fit the old index
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(CrushMatch::Table)
+                    .and_where(
+                        Expr::col(CrushMatch::SeasonId).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(Season::Id).min())
+                                .from(Season::Table)
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crush_match_identities")
+                    .table(CrushMatch::Table)
+                    .col(CrushMatch::IdentityAId)
+                    .col(CrushMatch::IdentityBId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CrushMatch::Table)
+                    .drop_column(CrushMatch::SeasonId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_voter_season_id")
+                    .table(Voter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .drop_column(Voter::SeasonId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Season::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Season {
+    Table,
+    Id,
+    Name,
+    StartsAt,
+    EndsAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    SeasonId,
+}
+
+#[derive(DeriveIden)]
+enum CrushMatch {
+    Table,
+    SeasonId,
+    IdentityAId,
+    IdentityBId,
+}
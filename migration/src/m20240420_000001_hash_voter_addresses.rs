@@ -0,0 +1,174 @@
+use std::net::IpAddr;
+
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+use sha2::Sha256;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .add_column(ColumnDef::new(Voter::Network).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        let rows = db
+            .query_all(
+                backend.build(
+                    &Query::select()
+                        .columns([Voter::Id, Voter::Address])
+                        .from(Voter::Table)
+                        .to_owned(),
+                ),
+            )
+            .await?;
+
+        // only raw addresses are hashed, so the migration can be re-run
+        let mut raw = Vec::new();
+        for row in rows {
+            let id: i32 = row.try_get("", "id")?;
+            let address: String = row.try_get("", "address")?;
+
+            if let Ok(ip) = address.trim().parse::<IpAddr>() {
+                raw.push((id, ip.to_canonical()));
+            }
+        }
+
+        if !raw.is_empty() {
+            let secret = address_secret()?;
+
+            for (id, ip) in raw {
+                db.execute(
+                    backend.build(
+                        &Query::update()
+                            .table(Voter::Table)
+                            .value(Voter::Address, hmac_hex(&secret, &ip.to_string()))
+                            .value(
+                                Voter::Network,
+                                network(ip).map(|network| hmac_hex(&secret, &network)),
+                            )
+                            .and_where(Expr::col(Voter::Id).eq(id))
+                            .to_owned(),
+                    ),
+                )
+                .await?;
+            }
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_address")
+                    .table(Voter::Table)
+                    .col(Voter::Address)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_voter_network")
+                    .table(Voter::Table)
+                    .col(Voter::Network)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // the hashes can't be turned back into addresses
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_voter_network")
+                    .table(Voter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_voter_address")
+                    .table(Voter::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Voter::Table)
+                    .drop_column(Voter::Network)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// `settings.address_hash.secret` of the app config, the one the app hashes
+/// addresses with
+fn address_secret() -> Result<String, DbErr> {
+    let environment: loco_rs::environment::Environment =
+        loco_rs::environment::resolve_from_env().into();
+    let config = environment
+        .load()
+        .map_err(|err| DbErr::Migration(format!("could not load the app config: {}", err)))?;
+
+    config
+        .settings
+        .as_ref()
+        .and_then(|settings| settings.pointer("/address_hash/secret"))
+        .and_then(|secret| secret.as_str())
+        .filter(|secret| !secret.is_empty())
+        .map(ToString::to_string)
+        .ok_or_else(|| {
+            DbErr::Migration(
+                "settings.address_hash.secret is needed to hash voter addresses".into(),
+            )
+        })
+}
+
+fn hmac_hex(secret: &str, address: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(address.as_bytes());
+
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// The /24 (IPv4) or /48 (IPv6) of the address
+fn network(ip: IpAddr) -> Option<String> {
+    let prefix = match ip {
+        IpAddr::V4(_) => 24,
+        IpAddr::V6(_) => 48,
+    };
+
+    IpNet::new(ip, prefix)
+        .ok()
+        .map(|network| network.trunc().to_string())
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    Id,
+    Address,
+    Network,
+}
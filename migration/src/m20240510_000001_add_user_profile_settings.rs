@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // no foreign key, SQLite can't add one to an existing table
+        let columns = [
+            ColumnDef::new(User::OwnerIdentityId).integer().to_owned(),
+            ColumnDef::new(User::ClaimedAt)
+                .timestamp_with_time_zone()
+                .to_owned(),
+            ColumnDef::new(User::Hidden)
+                .boolean()
+                .not_null()
+                .default(false)
+                .to_owned(),
+            ColumnDef::new(User::HideCount)
+                .boolean()
+                .not_null()
+                .default(false)
+                .to_owned(),
+            ColumnDef::new(User::VotesClosed)
+                .boolean()
+                .not_null()
+                .default(false)
+                .to_owned(),
+        ];
+
+        // SQLite only adds one column per statement
+        for mut column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(User::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            User::VotesClosed,
+            User::HideCount,
+            User::Hidden,
+            User::ClaimedAt,
+            User::OwnerIdentityId,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(User::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    OwnerIdentityId,
+    ClaimedAt,
+    Hidden,
+    HideCount,
+    VotesClosed,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VoteFlag::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VoteFlag::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(VoteFlag::VoterId).integer().not_null())
+                    .col(ColumnDef::new(VoteFlag::Kind).string().not_null())
+                    .col(ColumnDef::new(VoteFlag::Detail).string().not_null())
+                    .col(
+                        ColumnDef::new(VoteFlag::Dismissed)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(VoteFlag::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_vote_flag_voter_id")
+                            .from_tbl(VoteFlag::Table)
+                            .from_col(VoteFlag::VoterId)
+                            .to_tbl(Voter::Table)
+                            .to_col(Voter::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    // the analysis can be re-run, a vote is flagged once per kind
+                    .index(
+                        Index::create()
+                            .name("idx_vote_flag_voter_kind")
+                            .col(VoteFlag::VoterId)
+                            .col(VoteFlag::Kind)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VoteFlag::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VoteFlag {
+    Table,
+    Id,
+    VoterId,
+    Kind,
+    Detail,
+    Dismissed,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Voter {
+    Table,
+    Id,
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use crate::{common::settings::AnomalySettings, models::vote_flag::FlagKind};
+
+/// What the analysis looks at in a vote
+#[derive(Debug, Clone)]
+pub struct VoteSample {
+    pub voter_id: i32,
+    pub voted_user_id: i32,
+    /// The stored /24 or /48 of the voter
+    pub network: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A vote the analysis found suspicious
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flag {
+    pub voter_id: i32,
+    pub kind: FlagKind,
+    pub detail: String,
+}
+
+/// Looks for votes that look coordinated, each vote is flagged at most once
+/// per kind
+pub fn detect(settings: &AnomalySettings, votes: &[VoteSample]) -> Vec<Flag> {
+    let mut by_user: BTreeMap<i32, Vec<&VoteSample>> = BTreeMap::new();
+    for vote in votes {
+        by_user.entry(vote.voted_user_id).or_default().push(vote);
+    }
+
+    let mut flags = Vec::new();
+    for votes in by_user.values_mut() {
+        votes.sort_by_key(|vote| vote.created_at);
+
+        flags.extend(network_clusters(settings, votes));
+        flags.extend(spikes(settings, votes));
+        flags.extend(even_timing(settings, votes));
+    }
+
+    let mut seen = HashSet::new();
+    flags.retain(|flag| seen.insert((flag.voter_id, flag.kind)));
+
+    flags
+}
+
+/// Many votes for the user from the same /24 or /48
+fn network_clusters(settings: &AnomalySettings, votes: &[&VoteSample]) -> Vec<Flag> {
+    let mut by_network: HashMap<&str, Vec<&VoteSample>> = HashMap::new();
+    for vote in votes {
+        if let Some(network) = &vote.network {
+            by_network.entry(network).or_default().push(vote);
+        }
+    }
+
+    by_network
+        .into_iter()
+        .filter(|(_, votes)| votes.len() >= settings.network_votes as usize)
+        .flat_map(|(_, votes)| {
+            // the network is only stored hashed
+            let detail = format!("{} votes from the same network", votes.len());
+
+            votes.into_iter().map(move |vote| Flag {
+                voter_id: vote.voter_id,
+                kind: FlagKind::NetworkCluster,
+                detail: detail.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Hours with far more votes than the hours before them
+fn spikes(settings: &AnomalySettings, votes: &[&VoteSample]) -> Vec<Flag> {
+    let mut by_hour: BTreeMap<i64, Vec<&VoteSample>> = BTreeMap::new();
+    for vote in votes {
+        by_hour
+            .entry(vote.created_at.timestamp().div_euclid(60 * 60))
+            .or_default()
+            .push(vote);
+    }
+
+    let baseline_hours = i64::from(settings.baseline_hours.max(1));
+    let mut flags = Vec::new();
+    for (hour, hour_votes) in &by_hour {
+        let before = by_hour
+            .range(hour - baseline_hours..*hour)
+            .map(|(_, votes)| votes.len())
+            .sum::<usize>();
+        let baseline = before as f64 / baseline_hours as f64;
+
+        let count = hour_votes.len();
+        if count < settings.spike_votes as usize
+            || (count as f64) < settings.spike_factor * baseline.max(1.0)
+        {
+            continue;
+        }
+
+        let detail = format!(
+            "{} votes in an hour against a baseline of {:.1}",
+            count, baseline
+        );
+        flags.extend(hour_votes.iter().map(|vote| Flag {
+            voter_id: vote.voter_id,
+            kind: FlagKind::Spike,
+            detail: detail.clone(),
+        }));
+    }
+
+    flags
+}
+
+/// Bursts of consecutive votes cast at nearly the same interval, like a
+/// script on a timer
+fn even_timing(settings: &AnomalySettings, votes: &[&VoteSample]) -> Vec<Flag> {
+    let size = settings.even_votes.max(3) as usize;
+    let mut flags = Vec::new();
+
+    for window in votes.windows(size) {
+        let gaps: Vec<f64> = window
+            .windows(2)
+            .map(|pair| {
+                (pair[1].created_at - pair[0].created_at).num_milliseconds() as f64 / 1000.0
+            })
+            .collect();
+
+        if gaps
+            .iter()
+            .any(|gap| *gap <= 0.0 || *gap > settings.even_max_gap as f64)
+        {
+            continue;
+        }
+
+        let mean = gaps.iter().sum::<f64>() / gaps.len() as f64;
+        let variance = gaps.iter().map(|gap| (gap - mean).powi(2)).sum::<f64>() / gaps.len() as f64;
+        if variance.sqrt() / mean > settings.even_max_variation {
+            continue;
+        }
+
+        let detail = format!("{} votes {:.0}s apart", window.len(), mean);
+        flags.extend(window.iter().map(|vote| Flag {
+            voter_id: vote.voter_id,
+            kind: FlagKind::EvenTiming,
+            detail: detail.clone(),
+        }));
+    }
+
+    flags
+}
@@ -2,6 +2,7 @@ use std::{net::SocketAddr, path::Path};
 
 use async_trait::async_trait;
 use axum::Router as AxumRouter;
+use chrono::{DateTime, FixedOffset};
 use lazy_static::lazy_static;
 use loco_rs::{
     app::Hooks,
@@ -11,12 +12,22 @@ use loco_rs::{
     environment::Environment,
     prelude::*,
     task::Tasks,
-    worker::Processor,
+    worker::{AppWorker, Processor},
 };
 use migration::Migrator;
-use sea_orm::DatabaseConnection;
-
-use crate::{controllers, initializers, models::_entities::user};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serde::Deserialize;
+
+use crate::{
+    controllers, initializers,
+    models::_entities::{
+        blocked_username, crush_match, rate_limit_bucket, report, season, user,
+        username_verification, vote_flag, voter, voter_identity,
+    },
+    tasks,
+    utils::address_hash::AddressHasher,
+    workers,
+};
 
 lazy_static! {
     pub static ref REQWEST_CLIENT: ReqwestClient = ReqwestClient::new().unwrap();
@@ -42,6 +53,28 @@ impl ReqwestClient {
     }
 }
 
+#[derive(Deserialize)]
+struct SeasonFixture {
+    name: String,
+    starts_at: DateTime<FixedOffset>,
+    ends_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Deserialize)]
+struct UserFixture {
+    username: String,
+}
+
+/// Voters reference the user they voted for by username, they go into the
+/// active season. Addresses are already hashed, and voters are keyed on their
+/// address.
+#[derive(Deserialize)]
+struct VoterFixture {
+    address: String,
+    network: Option<String>,
+    username: String,
+}
+
 pub struct App;
 #[async_trait]
 impl Hooks for App {
@@ -68,27 +101,96 @@ impl Hooks for App {
             .prefix("/api")
             .add_route(controllers::vote::routes())
             .add_route(controllers::leaderboard::routes())
+            .add_route(controllers::identity::routes())
+            .add_route(controllers::matches::routes())
+            .add_route(controllers::users::routes())
+            .add_route(controllers::profile::routes())
+            .add_route(controllers::reports::routes())
+            .add_route(controllers::seasons::routes())
+            .add_route(controllers::admin::routes())
     }
 
     async fn truncate(db: &DatabaseConnection) -> Result<()> {
+        truncate_table(db, crush_match::Entity).await?;
+        truncate_table(db, vote_flag::Entity).await?;
+        truncate_table(db, voter::Entity).await?;
+        truncate_table(db, season::Entity).await?;
+        truncate_table(db, voter_identity::Entity).await?;
+        truncate_table(db, report::Entity).await?;
         truncate_table(db, user::Entity).await?;
+        truncate_table(db, username_verification::Entity).await?;
+        truncate_table(db, blocked_username::Entity).await?;
+        truncate_table(db, rate_limit_bucket::Entity).await?;
         Ok(())
     }
 
-    async fn seed(_db: &DatabaseConnection, _base: &Path) -> Result<()> {
-        unimplemented!("not gonna implement")
+    async fn seed(db: &DatabaseConnection, base: &Path) -> Result<()> {
+        let seasons: Vec<SeasonFixture> =
+            serde_yaml::from_str(&std::fs::read_to_string(base.join("season.yaml"))?)?;
+        for fixture in seasons {
+            season::ActiveModel {
+                name: ActiveValue::set(fixture.name),
+                starts_at: ActiveValue::set(fixture.starts_at),
+                ends_at: ActiveValue::set(fixture.ends_at),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+        let season_id = season::Model::find_active(db)
+            .await?
+            .map(|season| season.id);
+
+        let users: Vec<UserFixture> =
+            serde_yaml::from_str(&std::fs::read_to_string(base.join("user.yaml"))?)?;
+        for fixture in users {
+            user::Model::add(db, &fixture.username.to_lowercase()).await?;
+        }
+
+        let voters: Vec<VoterFixture> =
+            serde_yaml::from_str(&std::fs::read_to_string(base.join("voter.yaml"))?)?;
+        for fixture in voters {
+            let voted_user =
+                user::Model::find_by_username(db, &fixture.username.to_lowercase()).await?;
+
+            voter::ActiveModel {
+                ip_hash: ActiveValue::set(Some(fixture.address.clone())),
+                address: ActiveValue::set(fixture.address),
+                network: ActiveValue::set(fixture.network),
+                voted_user_id: ActiveValue::set(voted_user.id),
+                season_id: ActiveValue::set(season_id),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(())
     }
 
-    fn connect_workers<'a>(_p: &'a mut Processor, _ctx: &'a AppContext) {
-        unimplemented!("not gonna implement");
+    fn connect_workers<'a>(p: &'a mut Processor, ctx: &'a AppContext) {
+        p.register(workers::verify_vote::VerifyVoteWorker::build(ctx));
     }
 
-    fn register_tasks(_tasks: &mut Tasks) {
-        unimplemented!("not gonna implement");
+    fn register_tasks(tasks: &mut Tasks) {
+        tasks.register(tasks::stats::Stats);
+        tasks.register(tasks::purge_voters::PurgeVoters);
+        tasks.register(tasks::ban_username::BanUsername);
+        tasks.register(tasks::prune_orphans::PruneOrphans);
+        tasks.register(tasks::start_season::StartSeason);
+        tasks.register(tasks::detect_anomalies::DetectAnomalies);
     }
 
-    async fn initializers(_ctx: &AppContext) -> Result<Vec<Box<dyn Initializer>>> {
-        Ok(vec![Box::new(initializers::ip_getter::IPGetterInitializer)])
+    async fn initializers(ctx: &AppContext) -> Result<Vec<Box<dyn Initializer>>> {
+        // refuse to boot without the secret voter addresses are hashed with
+        AddressHasher::from_context(ctx)?;
+
+        Ok(vec![
+            Box::new(initializers::ip_getter::IPGetterInitializer),
+            Box::new(initializers::verifiers::VerifiersInitializer),
+            Box::new(initializers::ip_reputation::IpReputationInitializer),
+            Box::new(initializers::rate_limit::RateLimitInitializer),
+        ])
     }
 
     async fn serve(app: AxumRouter, server_config: ServeParams) -> Result<()> {
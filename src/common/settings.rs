@@ -1,14 +1,435 @@
 // put this in src/common/settings.rs
+use ipnet::IpNet;
 use loco_rs::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Settings {
     pub page_size: u64,
+    #[serde(default)]
+    pub captcha: CaptchaSettings,
+    #[serde(default)]
+    pub username_verifier: UsernameVerifierSettings,
+    #[serde(default)]
+    pub username_cache: UsernameCacheSettings,
+    #[serde(default)]
+    pub address_hash: AddressHashSettings,
+    #[serde(default)]
+    pub client_ip: ClientIpSettings,
+    #[serde(default)]
+    pub rate_limit: RateLimitSettings,
+    #[serde(default)]
+    pub voter_identity: VoterIdentitySettings,
+    #[serde(default)]
+    pub admin: AdminSettings,
+    #[serde(default)]
+    pub anomaly: AnomalySettings,
+    #[serde(default)]
+    pub risk: RiskSettings,
+    #[serde(default)]
+    pub ip_reputation: IpReputationSettings,
+    #[serde(default)]
+    pub leaderboard_cache: LeaderboardCacheSettings,
+}
+
+/// Which captcha provider `POST /api/vote` verifies tokens against
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptchaProvider {
+    #[default]
+    Recaptcha,
+    Hcaptcha,
+    Turnstile,
+    /// Deterministic verifier for development and tests, never calls out
+    Fake,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct CaptchaSettings {
+    #[serde(default)]
+    pub provider: CaptchaProvider,
+    /// Provider secret, required by every provider except `fake`
+    pub secret: Option<String>,
+    /// Minimum score to accept (reCAPTCHA v3), ignored when the provider
+    /// does not return a score
+    pub min_score: Option<f32>,
+    /// The only token the `fake` provider accepts, any non-empty token is
+    /// accepted when unset
+    pub fake_token: Option<String>,
+    /// Score reported by the `fake` provider
+    pub fake_score: Option<f32>,
+}
+
+/// How usernames submitted to `POST /api/vote` are checked for existence
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsernameVerifierBackend {
+    /// Scrapes the profile page on threads.net
+    #[default]
+    Threads,
+    /// Accepts the usernames in `allowlist`, or every username when it is
+    /// empty
+    Allowlist,
+    /// Accepts the usernames listed in the YAML file at `fixture_path`
+    Fixture,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct UsernameVerifierSettings {
+    #[serde(default)]
+    pub backend: UsernameVerifierBackend,
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    pub fixture_path: Option<String>,
+}
+
+/// Caches username lookups in the `username_verification` table
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UsernameCacheSettings {
+    #[serde(default = "default_true")]
+    pub enable: bool,
+    /// Seconds an `exists`/`not_found` result is reused for
+    #[serde(default = "default_username_cache_ttl")]
+    pub ttl: u64,
+    /// Seconds a failed lookup is reused for, keeps us from hammering threads
+    /// while it is down
+    #[serde(default = "default_username_cache_error_ttl")]
+    pub error_ttl: u64,
+}
+
+impl Default for UsernameCacheSettings {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            ttl: default_username_cache_ttl(),
+            error_ttl: default_username_cache_error_ttl(),
+        }
+    }
+}
+
+/// Voter addresses are stored as an HMAC keyed with `secret`
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct AddressHashSettings {
+    pub secret: Option<String>,
+    /// The secret being rotated out. Votes hashed with it are re-hashed with
+    /// `secret` the next time their voter shows up, so dedup keeps working.
+    pub previous_secret: Option<String>,
+}
+
+/// Headers a proxy can pass the client address in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientIpHeader {
+    /// `x-envoy-external-address`
+    Envoy,
+    /// `x-forwarded-for`
+    XForwardedFor,
+    /// `forwarded` (RFC 7239)
+    Forwarded,
+    /// `cf-connecting-ip`
+    CfConnectingIp,
+}
+
+/// Which proxies are trusted to tell the client address, requests from any
+/// other peer are identified by their socket address
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ClientIpSettings {
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNet>,
+    /// Checked in order, the first one carrying an address wins
+    #[serde(default)]
+    pub headers: Vec<ClientIpHeader>,
+}
+
+/// Where the rate limit buckets are kept
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitBackend {
+    /// In process, every instance limits on its own
+    #[default]
+    Memory,
+    /// In the `rate_limit_bucket` table, shared by every instance
+    Postgres,
+}
+
+/// Token buckets per client address and route
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RateLimitSettings {
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub backend: RateLimitBackend,
+    /// Answer `503` while the store isn't working instead of letting
+    /// requests through unlimited
+    #[serde(default)]
+    pub fail_closed: bool,
+    /// Routes without a rule aren't limited
+    #[serde(default)]
+    pub rules: Vec<RateLimitRule>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RateLimitRule {
+    /// Route path as registered, e.g. `/api/users/:username`
+    pub path: String,
+    /// Any method when unset
+    pub method: Option<String>,
+    /// Requests a client can make at once
+    pub burst: u32,
+    /// Requests a client gets back per minute
+    pub per_minute: u32,
+}
+
+/// What anonymous votes are deduplicated on
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoterIdentityMode {
+    /// The exact client address
+    #[default]
+    ExactIp,
+    /// IPv4 addresses as they are, IPv6 addresses collapsed to their /64
+    IpPrefix,
+    /// A signed voter cookie, at most `per_ip_cap` votes per exact address
+    Cookie,
+    /// A signed voter cookie, at most `per_ip_cap` votes per address
+    /// grouped as `composite_cap` says
+    Composite,
+}
+
+/// What the cap of the composite mode counts votes per
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeCap {
+    /// IPv4 addresses as they are, IPv6 addresses collapsed to their /64
+    #[default]
+    IpPrefix,
+    /// The /24 (IPv4) or /48 (IPv6) of the address
+    Network,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VoterIdentitySettings {
+    #[serde(default)]
+    pub mode: VoterIdentityMode,
+    /// Signs the voter cookie, required by the cookie modes
+    pub cookie_secret: Option<String>,
+    #[serde(default = "default_per_ip_cap")]
+    pub per_ip_cap: u32,
+    #[serde(default)]
+    pub composite_cap: CompositeCap,
+    /// Only send the voter cookie over HTTPS, turn off to test over plain
+    /// HTTP
+    #[serde(default = "default_cookie_secure")]
+    pub cookie_secure: bool,
+}
+
+impl Default for VoterIdentitySettings {
+    fn default() -> Self {
+        Self {
+            mode: VoterIdentityMode::default(),
+            cookie_secret: None,
+            per_ip_cap: default_per_ip_cap(),
+            composite_cap: CompositeCap::default(),
+            cookie_secure: default_cookie_secure(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct AdminSettings {
+    /// Bearer token of the `/api/admin` routes, they are disabled when unset
+    pub token: Option<String>,
+}
+
+/// Thresholds of the vote anomaly analysis
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AnomalySettings {
+    /// Votes for one user from one /24 or /48 that get them flagged
+    pub network_votes: u32,
+    /// Hours before an hour its baseline is averaged over
+    pub baseline_hours: u32,
+    /// An hour is a spike when it has this many times the baseline...
+    pub spike_factor: f64,
+    /// ...and at least this many votes
+    pub spike_votes: u32,
+    /// Consecutive votes checked for even timing
+    pub even_votes: u32,
+    /// Gaps between the votes vary less than this (standard deviation over
+    /// mean) in an evenly timed burst
+    pub even_max_variation: f64,
+    /// Longest gap between the votes of a burst, in seconds
+    pub even_max_gap: u64,
+    /// Shadows the flagged votes too, so the later votes of their voters
+    /// don't count either
+    pub shadow: bool,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            network_votes: 5,
+            baseline_hours: 24,
+            spike_factor: 5.0,
+            spike_votes: 10,
+            even_votes: 6,
+            even_max_variation: 0.1,
+            even_max_gap: 10 * 60,
+            shadow: false,
+        }
+    }
+}
+
+/// What happens to votes scoring `high_score` or more
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HighRiskAction {
+    /// The vote is refused
+    #[default]
+    Reject,
+    /// The vote is recorded and answered as usual, but shadowed so it never
+    /// counts
+    Shadow,
+}
+
+/// Scores every `POST /api/vote` from 0 (looks like a person) to 1 (looks
+/// automated)
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct RiskSettings {
+    pub enable: bool,
+    /// Votes scoring at least this need to pass the extra challenge
+    pub challenge_score: f32,
+    /// Votes scoring at least this are handled with `high_action`
+    pub high_score: f32,
+    pub high_action: HighRiskAction,
+    /// Minutes of prior activity of the address looked at
+    pub window_minutes: u32,
+    /// Votes from the address in the window before it adds to the score
+    pub address_votes: u32,
+    /// Votes from the /24 or /48 of the address in the window before it adds
+    /// to the score
+    pub network_votes: u32,
+    /// Provider of the extra challenge, required when risk scoring or an IP
+    /// list can ask for it. Must differ from the regular captcha.
+    pub challenge: Option<CaptchaSettings>,
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            challenge_score: 0.4,
+            high_score: 0.7,
+            high_action: HighRiskAction::default(),
+            window_minutes: 60,
+            address_votes: 3,
+            network_votes: 10,
+            challenge: None,
+        }
+    }
+}
+
+/// What `POST /api/vote` does with votes from a listed address
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IpListPolicy {
+    /// The vote is refused
+    Block,
+    /// The vote needs to pass the extra challenge of the risk scoring
+    Challenge,
+    /// The vote is recorded but flagged, so it doesn't count until a
+    /// moderator dismisses the flag
+    Flag,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IpListSettings {
+    /// Shown in the flags of the votes, e.g. `tor`
+    pub name: String,
+    /// File with one address or CIDR per line, `#` starts a comment
+    pub path: String,
+    pub policy: IpListPolicy,
+}
+
+/// Local lists of addresses votes are checked against, e.g. Tor exit nodes
+/// or hosting ranges
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct IpReputationSettings {
+    pub lists: Vec<IpListSettings>,
+    /// Seconds between checks for changed list files, 0 only loads them at
+    /// boot
+    pub reload_interval: u64,
+}
+
+impl Default for IpReputationSettings {
+    fn default() -> Self {
+        Self {
+            lists: Vec::new(),
+            reload_interval: 60,
+        }
+    }
+}
+
+/// Keeps leaderboard pages in process, votes and unvotes invalidate them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LeaderboardCacheSettings {
+    pub enable: bool,
+    /// Seconds a page is served from the cache at most, votes confirmed
+    /// elsewhere show up after this
+    pub ttl: u64,
+    /// Seconds a page is kept to be served, marked stale, while the database
+    /// is unavailable
+    pub stale_ttl: u64,
+    /// Pages kept at most
+    pub max_entries: usize,
+}
+
+impl Default for LeaderboardCacheSettings {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            ttl: 10,
+            stale_ttl: 10 * 60,
+            max_entries: 1000,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_username_cache_ttl() -> u64 {
+    60 * 60 * 24
+}
+
+fn default_username_cache_error_ttl() -> u64 {
+    30
+}
+
+fn default_per_ip_cap() -> u32 {
+    5
+}
+
+fn default_cookie_secure() -> bool {
+    true
 }
 
 impl Settings {
     pub fn from_json(value: &serde_json::Value) -> Result<Self> {
         Ok(serde_json::from_value(value.clone())?)
     }
+
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        let settings = ctx
+            .config
+            .settings
+            .as_ref()
+            .ok_or_else(|| Error::Message("missing `settings` in config".to_string()))?;
+
+        Self::from_json(settings)
+    }
 }
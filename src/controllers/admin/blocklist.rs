@@ -0,0 +1,61 @@
+use axum::http::StatusCode;
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use serde::Deserialize;
+
+use super::AdminAuth;
+use crate::{models::_entities::blocked_username, views::admin::BlockedUsernameResponse};
+
+#[derive(Deserialize)]
+pub struct BlockRequest {
+    username: String,
+    reason: Option<String>,
+}
+
+pub async fn list(_: AdminAuth, State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let blocked = blocked_username::Model::list(&ctx.db).await?;
+
+    format::json(
+        blocked
+            .into_iter()
+            .map(BlockedUsernameResponse::from)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Blocks a username from being voted for, votes it already got stay
+pub async fn block(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Json(params): Json<BlockRequest>,
+) -> Result<impl IntoResponse> {
+    let username = params.username.to_lowercase();
+
+    if username.is_empty() || username.len() > 30 {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("LENGTH_INVALID", "Username is too long/short"),
+        ));
+    }
+
+    let blocked = blocked_username::Model::block(&ctx.db, &username, params.reason).await?;
+
+    format::json(BlockedUsernameResponse::from(blocked))
+}
+
+pub async fn unblock(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    blocked_username::Model::unblock(&ctx.db, &username.to_lowercase())
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("NOT_BLOCKED", "Username is not blocked"),
+            ),
+            err => err.into(),
+        })?;
+
+    Ok(StatusCode::OK)
+}
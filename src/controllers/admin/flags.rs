@@ -0,0 +1,53 @@
+use axum::http::StatusCode;
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use serde::Serialize;
+
+use super::AdminAuth;
+use crate::{
+    leaderboard_cache::LeaderboardCache,
+    models::_entities::{user, vote_flag},
+    views::admin::FlaggedUserResponse,
+};
+
+#[derive(Serialize)]
+struct DismissResponse {
+    username: String,
+    dismissed: u64,
+}
+
+/// Users with flagged votes, those votes don't count until dismissed
+pub async fn list(_: AdminAuth, State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let flagged = vote_flag::Model::flagged_users(&ctx.db).await?;
+
+    format::json(
+        flagged
+            .into_iter()
+            .map(FlaggedUserResponse::from)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Dismisses the flags on the votes for a user, the votes count again
+pub async fn dismiss(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    let user = user::Model::find_by_username(&ctx.db, &username.to_lowercase())
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ),
+            err => err.into(),
+        })?;
+
+    let dismissed = vote_flag::Model::dismiss_for_user(&ctx.db, user.id).await?;
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    format::json(DismissResponse {
+        username: user.username,
+        dismissed,
+    })
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+
+use crate::common::settings::Settings;
+
+pub mod blocklist;
+pub mod flags;
+pub mod reports;
+pub mod users;
+pub mod votes;
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .add("/admin/users", get(users::list))
+        .add("/admin/users/:username", delete(users::delete))
+        .add("/admin/users/:username/suspend", post(users::suspend))
+        .add("/admin/users/:username/unsuspend", post(users::unsuspend))
+        .add("/admin/votes", get(votes::list))
+        .add("/admin/votes/invalidate", post(votes::invalidate))
+        .add("/admin/votes/shadow", post(votes::shadow))
+        .add("/admin/flags", get(flags::list))
+        .add("/admin/flags/:username/dismiss", post(flags::dismiss))
+        .add("/admin/reports", get(reports::list))
+        .add("/admin/reports/:id/resolve", post(reports::resolve))
+        .add("/admin/blocklist", get(blocklist::list))
+        .add("/admin/blocklist", post(blocklist::block))
+        .add("/admin/blocklist/:username", delete(blocklist::unblock))
+}
+
+/// Guards the admin routes, the request must carry
+/// `Authorization: Bearer <settings.admin.token>`
+pub struct AdminAuth;
+
+#[async_trait]
+impl FromRequestParts<AppContext> for AdminAuth {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, ctx: &AppContext) -> Result<Self> {
+        let settings = Settings::from_context(ctx)?;
+        let Some(expected) = settings.admin.token.filter(|token| !token.is_empty()) else {
+            return Err(Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("ADMIN_DISABLED", "Admin API is disabled"),
+            ));
+        };
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("Bearer "));
+
+        match token {
+            Some(token) if tokens_match(&expected, token.trim()) => Ok(Self),
+            _ => Err(Error::CustomError(
+                StatusCode::UNAUTHORIZED,
+                ErrorDetail::new("UNAUTHORIZED", "Admin token is missing or invalid"),
+            )),
+        }
+    }
+}
+
+/// Compares in constant time so the token can't be guessed byte by byte
+fn tokens_match(expected: &str, token: &str) -> bool {
+    expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,104 @@
+use axum::{extract::Query, http::StatusCode};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use tracing::error;
+
+use super::AdminAuth;
+use crate::{
+    common::settings::Settings,
+    leaderboard_cache::LeaderboardCache,
+    models::{
+        _entities::{report, user},
+        report::{ReportAction, ReportStatus, ResolveReportError},
+    },
+    views::admin::{AdminReport, AdminReportsResponse},
+};
+
+#[derive(Deserialize)]
+pub struct ListRequest {
+    /// Every report when unset
+    status: Option<ReportStatus>,
+    #[serde(default = "first_page")]
+    page: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveRequest {
+    action: ReportAction,
+    note: Option<String>,
+}
+
+fn first_page() -> u64 {
+    1
+}
+
+pub async fn list(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListRequest>,
+) -> Result<impl IntoResponse> {
+    let settings = Settings::from_context(&ctx)?;
+
+    let (reports, pagination) = report::Model::list(
+        &ctx.db,
+        params.status,
+        params.page.max(1),
+        settings.page_size,
+    )
+    .await?;
+
+    format::json(AdminReportsResponse {
+        pagination,
+        reports: reports.into_iter().map(AdminReport::from).collect(),
+    })
+}
+
+/// Closes a report, suspending the reported user if asked to
+pub async fn resolve(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    Json(params): Json<ResolveRequest>,
+) -> Result<impl IntoResponse> {
+    let report = report::Model::resolve(&ctx.db, id, params.action, params.note)
+        .await
+        .map_err(|err| {
+            let status_code;
+            let err_shorthand;
+
+            match err {
+                ResolveReportError::NotFound => {
+                    status_code = StatusCode::NOT_FOUND;
+                    err_shorthand = "REPORT_NOT_FOUND";
+                }
+                ResolveReportError::AlreadyClosed => {
+                    status_code = StatusCode::CONFLICT;
+                    err_shorthand = "REPORT_CLOSED";
+                }
+                ResolveReportError::ModelError(_) => {
+                    error!("Internal server error while resolving report: {}", err);
+
+                    status_code = StatusCode::INTERNAL_SERVER_ERROR;
+                    err_shorthand = "INTERNAL_ERROR";
+                }
+            }
+
+            Error::CustomError(
+                status_code,
+                ErrorDetail {
+                    error: Some(err_shorthand.to_string()),
+                    description: Some(err.to_string()),
+                },
+            )
+        })?;
+    if params.action == ReportAction::Suspend {
+        LeaderboardCache::shared(&ctx).await?.invalidate();
+    }
+
+    let user = user::Entity::find_by_id(report.user_id)
+        .one(&ctx.db)
+        .await?;
+
+    format::json(AdminReport::from((report, user)))
+}
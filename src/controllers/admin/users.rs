@@ -0,0 +1,115 @@
+use axum::{extract::Query, http::StatusCode};
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::AdminAuth;
+use crate::{
+    common::settings::Settings,
+    leaderboard_cache::LeaderboardCache,
+    models::_entities::user,
+    views::admin::{AdminUser, AdminUsersResponse},
+};
+
+#[derive(Deserialize)]
+pub struct ListRequest {
+    /// Part of the username
+    q: Option<String>,
+    #[serde(default = "first_page")]
+    page: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct SuspensionResponse {
+    username: String,
+    suspended: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct DeleteResponse {
+    username: String,
+    deleted_votes: u64,
+}
+
+fn first_page() -> u64 {
+    1
+}
+
+pub async fn list(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListRequest>,
+) -> Result<impl IntoResponse> {
+    let settings = Settings::from_context(&ctx)?;
+
+    let (users, pagination) =
+        user::Model::search(&ctx.db, &params.q, params.page.max(1), settings.page_size).await?;
+
+    format::json(AdminUsersResponse {
+        pagination,
+        users: users.into_iter().map(AdminUser::from).collect(),
+    })
+}
+
+/// Deletes a user with the votes it received
+pub async fn delete(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    let username = username.to_lowercase();
+
+    let deleted_votes = user::Model::delete_with_votes(&ctx.db, &username)
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ),
+            err => err.into(),
+        })?;
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    format::json(DeleteResponse {
+        username,
+        deleted_votes,
+    })
+}
+
+pub async fn suspend(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    set_suspended(&ctx, &username, true).await
+}
+
+pub async fn unsuspend(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    set_suspended(&ctx, &username, false).await
+}
+
+async fn set_suspended(
+    ctx: &AppContext,
+    username: &str,
+    suspended: bool,
+) -> Result<impl IntoResponse> {
+    let user = user::Model::set_suspended(&ctx.db, &username.to_lowercase(), suspended)
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ),
+            err => err.into(),
+        })?;
+    // suspended users are hidden from the leaderboard
+    LeaderboardCache::shared(ctx).await?.invalidate();
+
+    format::json(SuspensionResponse {
+        username: user.username,
+        suspended: user.suspended,
+    })
+}
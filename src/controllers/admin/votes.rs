@@ -0,0 +1,170 @@
+use axum::{extract::Query, http::StatusCode};
+use chrono::{DateTime, FixedOffset};
+use ipnet::IpNet;
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::AdminAuth;
+use crate::{
+    leaderboard_cache::LeaderboardCache,
+    models::_entities::voter,
+    utils::{
+        address_hash::{network, AddressHasher},
+        voter_resolver::collapse,
+    },
+    views::admin::AdminVote,
+};
+
+/// Votes listed at most, latest first
+const MAX_VOTES: u64 = 1000;
+
+#[derive(Deserialize)]
+pub struct ListRequest {
+    /// Client address the votes were cast from
+    address: Option<String>,
+    /// Network the votes were cast from, matched on their stored /24 or /48
+    /// so it can't be narrower
+    cidr: Option<IpNet>,
+}
+
+#[derive(Deserialize)]
+pub struct InvalidateRequest {
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+    cidr: Option<IpNet>,
+}
+
+#[derive(Serialize, Debug)]
+struct InvalidateResponse {
+    invalidated: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ShadowRequest {
+    address: Option<String>,
+    cidr: Option<IpNet>,
+    /// `false` lifts the shadow
+    #[serde(default = "default_shadow")]
+    shadow: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct ShadowResponse {
+    updated: u64,
+}
+
+fn default_shadow() -> bool {
+    true
+}
+
+pub async fn list(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Query(params): Query<ListRequest>,
+) -> Result<impl IntoResponse> {
+    let voters = match (params.address, params.cidr) {
+        (Some(address), None) => {
+            let hashes = address_hashes(&ctx, &address)?;
+            let hashes = hashes.iter().map(String::as_str).collect::<Vec<_>>();
+
+            voter::Model::find_by_ip_hashes(&ctx.db, &hashes, MAX_VOTES).await?
+        }
+        (None, Some(network)) => {
+            let networks = network_filter(&ctx, network)?;
+
+            voter::Model::find_in_network(&ctx.db, &networks, MAX_VOTES).await?
+        }
+        _ => return Err(filter_invalid()),
+    };
+
+    format::json(voters.into_iter().map(AdminVote::from).collect::<Vec<_>>())
+}
+
+/// Shadows the votes from an address or network. They keep being recorded
+/// and answered as usual but don't count, and new votes from the same voters
+/// are shadowed too.
+pub async fn shadow(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ShadowRequest>,
+) -> Result<impl IntoResponse> {
+    let updated = match (params.address, params.cidr) {
+        (Some(address), None) => {
+            let hashes = address_hashes(&ctx, &address)?;
+            let hashes = hashes.iter().map(String::as_str).collect::<Vec<_>>();
+
+            voter::Model::set_shadow_by_ip_hashes(&ctx.db, &hashes, params.shadow).await?
+        }
+        (None, Some(network)) => {
+            let networks = network_filter(&ctx, network)?;
+
+            voter::Model::set_shadow_in_network(&ctx.db, &networks, params.shadow).await?
+        }
+        _ => return Err(filter_invalid()),
+    };
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    format::json(ShadowResponse { updated })
+}
+
+/// Invalidates the votes cast in a time window, e.g. during a bot attack
+pub async fn invalidate(
+    _: AdminAuth,
+    State(ctx): State<AppContext>,
+    Json(params): Json<InvalidateRequest>,
+) -> Result<impl IntoResponse> {
+    if params.from >= params.to {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("WINDOW_INVALID", "`from` must be before `to`"),
+        ));
+    }
+
+    let networks = params
+        .cidr
+        .map(|cidr| network_filter(&ctx, cidr))
+        .transpose()?;
+    let invalidated =
+        voter::Model::invalidate(&ctx.db, params.from, params.to, networks.as_deref()).await?;
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    format::json(InvalidateResponse { invalidated })
+}
+
+/// The hashes votes from the address may be stored under
+fn address_hashes(ctx: &AppContext, address: &str) -> Result<Vec<String>> {
+    let hasher = AddressHasher::from_context(ctx)?;
+
+    // the address may have been collapsed to its /64 or network before
+    // hashing
+    let network = network(address).map(|network| hasher.hash(&network));
+
+    Ok([hasher.hash(address), hasher.hash(&collapse(address))]
+        .into_iter()
+        .chain(network)
+        .flat_map(|address| [Some(address.hash), address.previous_hash])
+        .flatten()
+        .collect())
+}
+
+/// The stored networks votes from `cidr` are matched on
+fn network_filter(ctx: &AppContext, cidr: IpNet) -> Result<Vec<String>> {
+    AddressHasher::from_context(ctx)?
+        .stored_networks(cidr)
+        .map_err(|err| {
+            Error::CustomError(
+                StatusCode::BAD_REQUEST,
+                ErrorDetail {
+                    error: Some("CIDR_INVALID".to_string()),
+                    description: Some(err.to_string()),
+                },
+            )
+        })
+}
+
+fn filter_invalid() -> Error {
+    Error::CustomError(
+        StatusCode::BAD_REQUEST,
+        ErrorDetail::new("FILTER_INVALID", "Pass either `address` or `cidr`"),
+    )
+}
@@ -0,0 +1,174 @@
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Extension,
+};
+use axum_client_ip::SecureClientIp;
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::vote::captcha_error;
+use crate::{
+    models::{_entities::voter_identity, voter_identity::ClaimError},
+    utils::{
+        get_ip::{get_ip, ClientIpResolver},
+        voter_token::voter_token,
+    },
+    verifiers::{
+        username::{find_profile_code, ProfileCode},
+        Verifiers,
+    },
+};
+
+#[derive(Deserialize, Debug)]
+pub struct ClaimRequest {
+    pub username: String,
+    #[serde(alias = "captcha_token")]
+    pub recaptcha_token: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ClaimResponse {
+    /// Sent back in the `x-voter-token` header, once verified it identifies
+    /// the voter
+    token: String,
+    /// Has to be added to the threads bio before calling `/identity/verify`
+    code: String,
+}
+
+#[derive(Serialize, Debug)]
+struct IdentityResponse {
+    username: String,
+    verified: bool,
+}
+
+/// Starts the challenge for a threads username
+async fn claim(
+    secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Extension(verifiers): Extension<Verifiers>,
+    Json(params): Json<ClaimRequest>,
+) -> Result<impl IntoResponse> {
+    let username = &params.username.to_lowercase();
+    let address = get_ip(&client_ip, &secure_ip, &headers);
+
+    verifiers
+        .captcha
+        .verify(&params.recaptcha_token, Some(&address))
+        .await
+        .map_err(captcha_error)?;
+
+    if username.is_empty() || username.len() > 30 {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("LENGTH_INVALID", "Username is too long/short"),
+        ));
+    }
+
+    let (identity, token) = voter_identity::Model::claim(&ctx.db, username)
+        .await
+        .map_err(|err| match err {
+            ClaimError::Pending => Error::CustomError(
+                StatusCode::CONFLICT,
+                ErrorDetail::new(
+                    "CLAIM_PENDING",
+                    "A challenge is already in progress for this username",
+                ),
+            ),
+            ClaimError::ModelError(err) => err.into(),
+        })?;
+
+    Ok(Json(ClaimResponse {
+        token,
+        code: identity.code.unwrap_or_default(),
+    }))
+}
+
+/// Looks for the challenge code on the threads profile
+async fn verify(
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Extension(verifiers): Extension<Verifiers>,
+) -> Result<impl IntoResponse> {
+    let token = voter_token(&headers).ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::UNAUTHORIZED,
+            ErrorDetail::new("MISSING_VOTER_TOKEN", "Voter token is missing"),
+        )
+    })?;
+
+    let identity = voter_identity::Model::find_by_pending_token(&ctx.db, token)
+        .await
+        .map_err(|_| {
+            Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("CLAIM_NOT_FOUND", "No challenge in progress for this token"),
+            )
+        })?;
+    let code = identity.code.clone().unwrap_or_default();
+
+    let result = find_profile_code(verifiers.username.as_ref(), &identity.username, &code)
+        .await
+        .map_err(|err| {
+            error!("Threads not working: {}", err);
+
+            Error::CustomError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorDetail::new("THREADS_NOT_WORKING", "Threads not working"),
+            )
+        })?;
+
+    match result {
+        ProfileCode::Found => {}
+        ProfileCode::Missing => {
+            return Err(Error::CustomError(
+                StatusCode::FORBIDDEN,
+                ErrorDetail::new("CODE_NOT_FOUND", "Code not found in the threads bio"),
+            ))
+        }
+        ProfileCode::ProfileNotFound => {
+            return Err(Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ))
+        }
+    }
+
+    let identity = identity.confirm(&ctx.db).await?;
+
+    Ok(Json(IdentityResponse {
+        username: identity.username,
+        verified: true,
+    }))
+}
+
+/// Gets the identity authenticated by the voter token
+async fn current(headers: HeaderMap, State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let identity = match voter_token(&headers) {
+        Some(token) => voter_identity::Model::find_verified_by_token(&ctx.db, token).await?,
+        None => None,
+    }
+    .ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::UNAUTHORIZED,
+            ErrorDetail::new(
+                "INVALID_VOTER_TOKEN",
+                "Voter token is invalid or unverified",
+            ),
+        )
+    })?;
+
+    Ok(Json(IdentityResponse {
+        verified: identity.is_verified(),
+        username: identity.username,
+    }))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .add("/identity", get(current))
+        .add("/identity/claim", post(claim))
+        .add("/identity/verify", post(verify))
+}
@@ -1,25 +1,123 @@
-use axum::{extract::Query, http::StatusCode};
+use std::sync::Arc;
+
+use axum::{extract::Query, http::StatusCode, response::Response};
 use loco_rs::{controller::ErrorDetail, prelude::*};
+use sea_orm::EntityTrait;
 use serde::Deserialize;
+use tracing::warn;
+
+use crate::{
+    common,
+    leaderboard_cache::{LeaderboardCache, LeaderboardKey},
+    models::{
+        _entities::{season, user},
+        user::LeaderboardWindow,
+    },
+    views::leaderboard::LeaderboardResponse,
+};
 
-use crate::{common, models::_entities::user, views::leaderboard::LeaderboardResponse};
+/// Set on pages served from the cache while the database is unavailable,
+/// with how many seconds ago they were read
+pub const STALE_HEADER: &str = "x-leaderboard-stale";
 
 #[derive(Deserialize)]
 struct LeaderboardRequest {
     username: Option<String>,
     page: u64,
+    #[serde(default)]
+    window: LeaderboardWindow,
+    /// Defaults to the latest season
+    season: Option<i32>,
 }
 
 async fn leaderboard(
     State(ctx): State<AppContext>,
     Query(params): Query<LeaderboardRequest>,
-) -> Result<impl IntoResponse> {
-    let settings = &ctx.config.settings.unwrap();
-    let settings = common::settings::Settings::from_json(settings)?;
+) -> Result<Response> {
+    let cache = LeaderboardCache::shared(&ctx).await?;
+    let key = LeaderboardKey {
+        season: params.season,
+        username: params.username.clone(),
+        window: params.window,
+        page: params.page,
+    };
+
+    if let Some(response) = cache.get(&key) {
+        return format::render().json(&*response);
+    }
+
+    let generation = cache.generation();
+    let read = read_leaderboard(&ctx, params).await;
+
+    respond(cache, key, generation, read)
+}
+
+/// Caches the page read from the database, or serves the cached one stale
+/// when the database failed
+pub fn respond(
+    cache: &LeaderboardCache,
+    key: LeaderboardKey,
+    generation: u64,
+    read: Result<LeaderboardResponse>,
+) -> Result<Response> {
+    match read {
+        Ok(response) => {
+            let response = Arc::new(response);
+            cache.insert(key, generation, response.clone());
+
+            format::render().json(&*response)
+        }
+        // missing seasons and pages aren't database failures
+        Err(err @ Error::CustomError(..)) => Err(err),
+        Err(err) => {
+            let Some((response, age)) = cache.get_stale(&key) else {
+                return Err(err);
+            };
+            warn!("Serving a stale leaderboard page: {}", err);
+
+            format::render()
+                .header(STALE_HEADER, age.as_secs())
+                .json(&*response)
+        }
+    }
+}
+
+async fn read_leaderboard(
+    ctx: &AppContext,
+    params: LeaderboardRequest,
+) -> Result<LeaderboardResponse> {
+    let settings = common::settings::Settings::from_context(ctx)?;
+
+    // pages start at 1
+    if params.page < 1 {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("PAGE_INVALID", "Pages start at 1"),
+        ));
+    }
+
+    let season = match params.season {
+        Some(id) => season::Entity::find_by_id(id).one(&ctx.db).await?,
+        None => season::Model::find_latest(&ctx.db).await?,
+    }
+    .ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("SEASON_NOT_FOUND", "Season does not exist"),
+        )
+    })?;
+
+    let username = &params.username.map(|u| u.to_lowercase());
 
-    let mut pagination =
-        user::Model::get_leaderboard_pagination(&ctx.db, settings.page_size, &params.username)
-            .await?;
+    // counted like the page is read, so `last` matches the season and window
+    let mut pagination = user::Model::get_leaderboard_pagination(
+        &ctx.db,
+        settings.page_size,
+        username,
+        Some(season.id),
+        params.window,
+    )
+    .await?;
     pagination.current = params.page;
 
     if params.page > pagination.last {
@@ -29,19 +127,17 @@ async fn leaderboard(
         ));
     }
 
-    let username = &params.username.map(|u| u.to_lowercase());
-
     let users = user::Model::find_leaderboard(
         &ctx.db,
         username,
-        params.page.try_into().unwrap(),
+        Some(season.id),
+        params.window,
+        params.page,
         settings.page_size,
     )
     .await?;
 
-    let res = format::json(LeaderboardResponse::new(users, pagination))?;
-
-    Ok(res)
+    Ok(LeaderboardResponse::new(season, users, pagination))
 }
 
 pub fn routes() -> Routes {
@@ -0,0 +1,78 @@
+use axum::{
+    extract::Query,
+    http::{HeaderMap, StatusCode},
+};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::_entities::{crush_match, season, voter_identity},
+    utils::voter_token::voter_token,
+};
+
+#[derive(Deserialize, Debug)]
+pub struct MatchesRequest {
+    /// Defaults to the latest season
+    season: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+struct MatchResponse {
+    username: String,
+    matched_at: String,
+}
+
+/// Lists the mutual crushes of the verified voter in a season, only the two
+/// people involved ever see a match
+async fn matches(
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Query(params): Query<MatchesRequest>,
+) -> Result<impl IntoResponse> {
+    let identity = match voter_token(&headers) {
+        Some(token) => voter_identity::Model::find_verified_by_token(&ctx.db, token).await?,
+        None => None,
+    }
+    .ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::UNAUTHORIZED,
+            ErrorDetail::new(
+                "INVALID_VOTER_TOKEN",
+                "Voter token is invalid or unverified",
+            ),
+        )
+    })?;
+
+    let season = match params.season {
+        Some(id) => Some(
+            season::Entity::find_by_id(id)
+                .one(&ctx.db)
+                .await?
+                .ok_or_else(|| {
+                    Error::CustomError(
+                        StatusCode::NOT_FOUND,
+                        ErrorDetail::new("SEASON_NOT_FOUND", "Season does not exist"),
+                    )
+                })?,
+        ),
+        None => season::Model::find_latest(&ctx.db).await?,
+    };
+    let Some(season) = season else {
+        return Ok(Json(Vec::new()));
+    };
+
+    let matches = crush_match::Model::find_for_identity(&ctx.db, identity.id, season.id)
+        .await?
+        .into_iter()
+        .map(|(crush_match, other)| MatchResponse {
+            username: other.username,
+            matched_at: crush_match.created_at.to_rfc3339(),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(matches))
+}
+
+pub fn routes() -> Routes {
+    Routes::new().add("/matches", get(matches))
+}
@@ -1,2 +1,9 @@
+pub mod admin;
+pub mod identity;
 pub mod leaderboard;
+pub mod matches;
+pub mod profile;
+pub mod reports;
+pub mod seasons;
+pub mod users;
 pub mod vote;
@@ -0,0 +1,92 @@
+use axum::http::{HeaderMap, StatusCode};
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use serde::Serialize;
+
+use super::vote::verified_identity;
+use crate::{
+    leaderboard_cache::LeaderboardCache,
+    models::{
+        _entities::{user, voter_identity},
+        user::ProfileSettings,
+    },
+};
+
+#[derive(Serialize, Debug)]
+struct ProfileResponse {
+    username: String,
+    hidden: bool,
+    hide_count: bool,
+    votes_closed: bool,
+}
+
+impl From<user::Model> for ProfileResponse {
+    fn from(user: user::Model) -> Self {
+        ProfileResponse {
+            username: user.username,
+            hidden: user.hidden,
+            hide_count: user.hide_count,
+            votes_closed: user.votes_closed,
+        }
+    }
+}
+
+/// Only the verified owner of the threads account manages its profile
+async fn owner(ctx: &AppContext, headers: &HeaderMap) -> Result<voter_identity::Model> {
+    verified_identity(ctx, headers).await?.ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::UNAUTHORIZED,
+            ErrorDetail::new("MISSING_VOTER_TOKEN", "Voter token is missing"),
+        )
+    })
+}
+
+async fn claimed_profile(ctx: &AppContext, headers: &HeaderMap) -> Result<user::Model> {
+    let identity = owner(ctx, headers).await?;
+
+    user::Model::find_by_owner(&ctx.db, identity.id)
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("PROFILE_NOT_CLAIMED", "Profile is not claimed yet"),
+            ),
+            err => err.into(),
+        })
+}
+
+/// Claims the profile of the threads account the voter token was verified
+/// for
+async fn claim(headers: HeaderMap, State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let identity = owner(&ctx, &headers).await?;
+
+    let user = user::Model::claim(&ctx.db, &identity.username, identity.id).await?;
+
+    format::json(ProfileResponse::from(user))
+}
+
+async fn current(headers: HeaderMap, State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let user = claimed_profile(&ctx, &headers).await?;
+
+    format::json(ProfileResponse::from(user))
+}
+
+async fn update(
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ProfileSettings>,
+) -> Result<impl IntoResponse> {
+    let user = claimed_profile(&ctx, &headers)
+        .await?
+        .update_settings(&ctx.db, params)
+        .await?;
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    format::json(ProfileResponse::from(user))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .add("/profile", get(current))
+        .add("/profile", put(update))
+        .add("/profile/claim", post(claim))
+}
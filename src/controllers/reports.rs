@@ -0,0 +1,94 @@
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Extension,
+};
+use axum_client_ip::SecureClientIp;
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::vote::captcha_error;
+use crate::{
+    models::{
+        _entities::{report, user},
+        report::FileReportError,
+    },
+    utils::{
+        address_hash::AddressHasher,
+        get_ip::{get_ip, ClientIpResolver},
+    },
+    verifiers::Verifiers,
+};
+
+/// Longest reason accepted, in characters
+const MAX_REASON_LENGTH: usize = 500;
+
+#[derive(Deserialize, Debug)]
+pub struct ReportRequest {
+    pub username: String,
+    pub reason: String,
+    #[serde(alias = "captcha_token")]
+    pub recaptcha_token: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ReportResponse {
+    id: i32,
+}
+
+/// Files a report against a user for the moderators to look at
+async fn report(
+    secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Extension(verifiers): Extension<Verifiers>,
+    Json(params): Json<ReportRequest>,
+) -> Result<impl IntoResponse> {
+    let username = &params.username.to_lowercase();
+    let address = get_ip(&client_ip, &secure_ip, &headers);
+
+    verifiers
+        .captcha
+        .verify(&params.recaptcha_token, Some(&address))
+        .await
+        .map_err(captcha_error)?;
+
+    let reason = params.reason.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REASON_LENGTH {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("REASON_INVALID", "Reason is too long/short"),
+        ));
+    }
+
+    let user = user::Model::find_by_username(&ctx.db, username)
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ),
+            err => err.into(),
+        })?;
+
+    // reporters are only kept hashed, like voters
+    let reporter = AddressHasher::from_context(&ctx)?.hash(&address);
+    let report = report::Model::file(&ctx.db, user.id, reason, &reporter.hash)
+        .await
+        .map_err(|err| match err {
+            FileReportError::AlreadyReported => Error::CustomError(
+                StatusCode::CONFLICT,
+                ErrorDetail::new(
+                    "ALREADY_REPORTED",
+                    "You already reported this user, a moderator will look at it",
+                ),
+            ),
+            FileReportError::ModelError(err) => err.into(),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(ReportResponse { id: report.id })))
+}
+
+pub fn routes() -> Routes {
+    Routes::new().add("/reports", post(report))
+}
@@ -0,0 +1,37 @@
+use loco_rs::prelude::*;
+use sea_orm::{EntityTrait, QueryOrder};
+
+use crate::{
+    models::_entities::season,
+    views::season::{HallOfFameEntry, SeasonResponse},
+};
+
+/// Lists every season, newest first, so past leaderboards can be browsed
+async fn list(State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let seasons = season::Entity::find()
+        .order_by_desc(season::Column::StartsAt)
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(SeasonResponse::from)
+        .collect::<Vec<_>>();
+
+    format::json(seasons)
+}
+
+/// Lists the winner of each ended season
+async fn hall_of_fame(State(ctx): State<AppContext>) -> Result<impl IntoResponse> {
+    let entries = season::Model::hall_of_fame(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|(season, winner)| HallOfFameEntry::new(season, winner))
+        .collect::<Vec<_>>();
+
+    format::json(entries)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .add("/seasons", get(list))
+        .add("/hall-of-fame", get(hall_of_fame))
+}
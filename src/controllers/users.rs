@@ -0,0 +1,55 @@
+use axum::http::StatusCode;
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+
+use crate::{
+    models::_entities::{season, user},
+    views::user::UserProfileResponse,
+};
+
+async fn profile(
+    State(ctx): State<AppContext>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    let username = username.to_lowercase();
+
+    let user = user::Model::find_by_username(&ctx.db, &username)
+        .await
+        .map_err(|err| match err {
+            ModelError::EntityNotFound => Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ),
+            err => err.into(),
+        })?;
+
+    if user.hidden || user.suspended {
+        return Err(Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+        ));
+    }
+
+    // ranked and counted like in the default leaderboard
+    let season_id = season::Model::find_latest(&ctx.db)
+        .await?
+        .map(|season| season.id);
+    let ranked = user::Model::find_ranked(&ctx.db, &user.username, season_id).await?;
+    let first_vote_at = user.find_first_vote_at(&ctx.db, season_id).await?;
+    let daily_votes = if user.hide_count {
+        Vec::new()
+    } else {
+        user.find_daily_votes(&ctx.db, season_id).await?
+    };
+
+    format::json(UserProfileResponse::new(
+        user.username,
+        user.hide_count,
+        ranked,
+        first_vote_at,
+        &daily_votes,
+    ))
+}
+
+pub fn routes() -> Routes {
+    Routes::new().add("/users/:username", get(profile))
+}
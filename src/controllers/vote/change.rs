@@ -0,0 +1,134 @@
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Extension,
+};
+use axum_client_ip::SecureClientIp;
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use serde::Serialize;
+use tracing::error;
+
+use super::{
+    active_season, captcha_error, check_ip_lists, check_risk, check_votable, verified_identity,
+    verify_challenge, vote::VoteRequest, vote_invalidated, voter_addresses,
+};
+use crate::{
+    ip_reputation::IpReputation,
+    leaderboard_cache::LeaderboardCache,
+    models::{
+        _entities::{crush_match, user, voter},
+        voter::ChangeVoteError,
+    },
+    utils::get_ip::{get_ip, ClientIpResolver},
+    verifiers::Verifiers,
+};
+
+#[derive(Serialize, Debug)]
+struct ChangeVoteResponse {
+    previous_voted_user: Option<String>,
+    voted_user: String,
+}
+
+/// Moves the vote to another user, the voter keeps their vote when the new
+/// username can't be verified
+pub async fn change(
+    secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+    Extension(verifiers): Extension<Verifiers>,
+    Extension(reputation): Extension<IpReputation>,
+    Json(params): Json<VoteRequest>,
+) -> Result<impl IntoResponse> {
+    let username = &params.username.to_lowercase();
+    let address = get_ip(&client_ip, &secure_ip, &headers);
+
+    let captcha = verifiers
+        .captcha
+        .verify(&params.recaptcha_token, Some(&address))
+        .await
+        .map_err(captcha_error)?;
+
+    if username.is_empty() || username.len() > 30 {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("LENGTH_INVALID", "Username is too long/short"),
+        ));
+    }
+
+    // screened like a new vote, or a blocked network could move votes around
+    let mut screening = check_ip_lists(&reputation, &address)?;
+
+    check_votable(&ctx, username).await?;
+
+    let season = active_season(&ctx).await?;
+    let identity = verified_identity(&ctx, &headers).await?;
+    let addresses = voter_addresses(&ctx, &address, &headers, false).await?;
+    let key = addresses.voter_key(identity.as_ref()).ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("NOT_FOUND", "Voter not found"),
+        )
+    })?;
+
+    check_risk(&ctx, &headers, &addresses, captcha.score, &mut screening).await?;
+    if screening.challenge {
+        verify_challenge(&verifiers, &address, params.challenge_token.as_deref()).await?;
+    }
+
+    match verifiers.username.exists(username).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(Error::CustomError(
+                StatusCode::NOT_FOUND,
+                ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+            ))
+        }
+        Err(err) => {
+            error!("Threads not working: {}", err);
+            return Err(Error::CustomError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorDetail::new("THREADS_NOT_WORKING", "Threads not working"),
+            ));
+        }
+    }
+
+    let voted_user_id = user::Model::add(&ctx.db, username).await?.id;
+
+    let screened = screening.screened();
+    let (previous, voter) = voter::Model::change(&ctx.db, key, season.id, voted_user_id, screened)
+        .await
+        .map_err(|err| {
+            let status_code;
+            let err_shorthand;
+
+            match err {
+                ChangeVoteError::NotFound => {
+                    status_code = StatusCode::NOT_FOUND;
+                    err_shorthand = "NOT_FOUND";
+                }
+                ChangeVoteError::Invalidated => return vote_invalidated(),
+                _ => {
+                    error!("Internal server error while changing vote: {}", err);
+
+                    status_code = StatusCode::INTERNAL_SERVER_ERROR;
+                    err_shorthand = "INTERNAL_ERROR";
+                }
+            }
+
+            Error::CustomError(
+                status_code,
+                ErrorDetail {
+                    error: Some(err_shorthand.to_string()),
+                    description: Some(err.to_string()),
+                },
+            )
+        })?;
+
+    crush_match::Model::detect(&ctx.db, &voter).await?;
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    Ok(Json(ChangeVoteResponse {
+        previous_voted_user: previous.map(|user| user.username),
+        voted_user: username.to_string(),
+    }))
+}
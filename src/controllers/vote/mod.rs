@@ -1,12 +1,347 @@
-use loco_rs::prelude::*;
+use axum::http::{HeaderMap, StatusCode};
+use chrono::{Duration, Utc};
+use loco_rs::{controller::ErrorDetail, model::ModelError, prelude::*};
+use tracing::{debug, error, warn};
 
+use crate::{
+    common::settings::{HighRiskAction, IpListPolicy, Settings},
+    ip_reputation::IpReputation,
+    models::{
+        _entities::{blocked_username, season, user, voter, voter_identity},
+        vote_flag::FlagKind,
+        voter::{Screened, VoterKey},
+    },
+    risk::{self, RiskLevel, RiskSignals},
+    utils::{
+        address_hash::{AddressHasher, VoterAddress},
+        voter_resolver::VoterIdentityResolver,
+        voter_token::voter_token,
+    },
+    verifiers::{captcha::CaptchaError, Verifiers},
+};
+
+pub mod change;
 pub mod status;
 pub mod unvote;
+#[allow(clippy::module_inception)]
 pub mod vote;
 
 pub fn routes() -> Routes {
     Routes::new()
         .add("/vote", post(vote::vote))
+        .add("/vote", put(change::change))
         .add("/vote", delete(unvote::unvote))
         .add("/vote/status", get(status::status))
 }
+
+/// Gets the verified identity of the voter when the request carries a voter
+/// token, anonymous voters are identified by their address instead
+pub(crate) async fn verified_identity(
+    ctx: &AppContext,
+    headers: &HeaderMap,
+) -> Result<Option<voter_identity::Model>> {
+    let Some(token) = voter_token(headers) else {
+        return Ok(None);
+    };
+
+    let identity = voter_identity::Model::find_verified_by_token(&ctx.db, token)
+        .await?
+        .ok_or_else(|| {
+            Error::CustomError(
+                StatusCode::UNAUTHORIZED,
+                ErrorDetail::new(
+                    "INVALID_VOTER_TOKEN",
+                    "Voter token is invalid or unverified",
+                ),
+            )
+        })?;
+
+    Ok(Some(identity))
+}
+
+/// How an anonymous voter is recognized, hashed like everything stored about
+/// their address
+pub(crate) struct VoterAddresses {
+    /// What the vote is deduplicated on, `None` when the voter can't be
+    /// recognized (no voter cookie yet)
+    pub key: Option<VoterAddress>,
+    /// The address votes are capped on, with the network of the client
+    pub address: VoterAddress,
+    pub cap: Option<u32>,
+    /// `Set-Cookie` value when a voter cookie was issued
+    pub set_cookie: Option<String>,
+}
+
+impl VoterAddresses {
+    /// Verified voters are recognized by their identity wherever they vote
+    /// from
+    pub fn voter_key(&self, identity: Option<&voter_identity::Model>) -> Option<VoterKey<'_>> {
+        match identity {
+            Some(identity) => Some(VoterKey::Identity(
+                identity.id,
+                self.key.as_ref().map(|key| key.hash.as_str()),
+            )),
+            None => self.key.as_ref().map(|key| VoterKey::Address(&key.hash)),
+        }
+    }
+}
+
+/// Resolves the voter with the configured strategy and hashes the result,
+/// moving their votes hashed with the secret being rotated out to the current
+/// one first
+pub(crate) async fn voter_addresses(
+    ctx: &AppContext,
+    ip: &str,
+    headers: &HeaderMap,
+    issue_cookie: bool,
+) -> Result<VoterAddresses> {
+    let resolved = VoterIdentityResolver::from_context(ctx)?.resolve(ip, headers, issue_cookie);
+    let hasher = AddressHasher::from_context(ctx)?;
+
+    let key = resolved.key.map(|key| hasher.hash(&key));
+    let network = hasher.hash(ip);
+    let address = VoterAddress {
+        network: network.network,
+        previous_network: network.previous_network,
+        ..hasher.hash(&resolved.cap_address)
+    };
+
+    if let Some(key) = &key {
+        voter::Model::rekey(&ctx.db, key, &address).await?;
+    }
+
+    Ok(VoterAddresses {
+        key,
+        address,
+        cap: resolved.cap,
+        set_cookie: resolved.set_cookie,
+    })
+}
+
+/// Gets the season votes are written into, past seasons are read-only
+pub(crate) async fn active_season(ctx: &AppContext) -> Result<season::Model> {
+    season::Model::find_active(&ctx.db).await?.ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::FORBIDDEN,
+            ErrorDetail::new("NO_ACTIVE_SEASON", "No season is running"),
+        )
+    })
+}
+
+/// Maps a failed captcha check to the error returned to the voter. The codes
+/// predate the other providers, clients still match on them.
+pub(crate) fn captcha_error(err: CaptchaError) -> Error {
+    let status_code;
+    let err_shorthand;
+
+    match &err {
+        CaptchaError::ProviderNotWorking(e) => {
+            error!("Captcha provider not working: {}", e);
+            status_code = StatusCode::SERVICE_UNAVAILABLE;
+            err_shorthand = "GOOGLE_NOT_WORKING";
+        }
+        CaptchaError::FailedToParse(e) => {
+            error!("Failed to parse captcha response: {}", e);
+            status_code = StatusCode::INTERNAL_SERVER_ERROR;
+            err_shorthand = "FAILED_TO_PARSE";
+        }
+        CaptchaError::MissingSecret => {
+            error!("Captcha secret is not configured");
+            status_code = StatusCode::INTERNAL_SERVER_ERROR;
+            err_shorthand = "INTERNAL_ERROR";
+        }
+        CaptchaError::Failed => {
+            status_code = StatusCode::FORBIDDEN;
+            err_shorthand = "RECAPTCHA_FAILED";
+        }
+    };
+
+    Error::CustomError(
+        status_code,
+        ErrorDetail {
+            error: Some(err_shorthand.to_string()),
+            description: Some(err.to_string()),
+        },
+    )
+}
+
+/// Moderators can block usernames or suspend users, and owners of a claimed
+/// profile can stop taking votes
+pub(crate) async fn check_votable(ctx: &AppContext, username: &str) -> Result<()> {
+    if blocked_username::Model::is_blocked(&ctx.db, username).await? {
+        return Err(Error::CustomError(
+            StatusCode::FORBIDDEN,
+            ErrorDetail::new("USERNAME_BLOCKED", "Username can't be voted for"),
+        ));
+    }
+
+    let user = match user::Model::find_by_username(&ctx.db, username).await {
+        Ok(user) => Some(user),
+        Err(ModelError::EntityNotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    if user.as_ref().is_some_and(|user| user.suspended) {
+        return Err(Error::CustomError(
+            StatusCode::FORBIDDEN,
+            ErrorDetail::new("USER_SUSPENDED", "User is suspended"),
+        ));
+    }
+    if user.is_some_and(|user| user.votes_closed) {
+        return Err(Error::CustomError(
+            StatusCode::FORBIDDEN,
+            ErrorDetail::new("VOTES_CLOSED", "User doesn't take votes"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returned when a moderator threw the vote out
+pub(crate) fn vote_invalidated() -> Error {
+    Error::CustomError(
+        StatusCode::FORBIDDEN,
+        ErrorDetail::new("VOTE_INVALIDATED", "Vote was invalidated"),
+    )
+}
+
+/// What the IP lists and the risk scoring ask of a vote
+#[derive(Default)]
+pub(crate) struct Screening {
+    /// The vote must pass the extra challenge
+    pub challenge: bool,
+    /// The vote is recorded shadowed
+    pub shadow: bool,
+    /// The lists with the `flag` policy the address is on
+    pub flag_lists: Vec<String>,
+}
+
+impl Screening {
+    /// What is stored with the vote, a flag when the address is on a list
+    /// with the `flag` policy
+    pub fn screened(&self) -> Screened {
+        Screened {
+            shadow: self.shadow,
+            flag: (!self.flag_lists.is_empty()).then(|| {
+                (
+                    FlagKind::IpList,
+                    format!("listed in {}", self.flag_lists.join(", ")),
+                )
+            }),
+        }
+    }
+}
+
+/// Looks the address up in the IP lists, votes from a list with the `block`
+/// policy are refused, as are votes from an address that can't be looked up
+pub(crate) fn check_ip_lists(reputation: &IpReputation, address: &str) -> Result<Screening> {
+    let mut screening = Screening::default();
+    if reputation.is_empty() {
+        return Ok(screening);
+    }
+
+    // the lists can't be checked, e.g. a proxy passed something else than an
+    // address, so the vote isn't let through unchecked
+    let Ok(ip) = address.parse() else {
+        warn!("Could not check {:?} against the IP lists", address);
+        return Err(Error::CustomError(
+            StatusCode::FORBIDDEN,
+            ErrorDetail::new("ADDRESS_UNCHECKED", "Could not check your address"),
+        ));
+    };
+
+    for list in reputation.lookup(ip) {
+        match list.policy {
+            IpListPolicy::Block => {
+                return Err(Error::CustomError(
+                    StatusCode::FORBIDDEN,
+                    ErrorDetail::new("ADDRESS_BLOCKED", "Votes from this network are refused"),
+                ))
+            }
+            IpListPolicy::Challenge => screening.challenge = true,
+            IpListPolicy::Flag => screening.flag_lists.push(list.name),
+        }
+    }
+
+    Ok(screening)
+}
+
+/// Scores the vote with the request signals and the recent votes from its
+/// address. Medium risk votes must pass the extra challenge, high risk votes
+/// are rejected or shadowed.
+pub(crate) async fn check_risk(
+    ctx: &AppContext,
+    headers: &HeaderMap,
+    addresses: &VoterAddresses,
+    captcha_score: Option<f32>,
+    screening: &mut Screening,
+) -> Result<()> {
+    let settings = Settings::from_context(ctx)?.risk;
+    if !settings.enable {
+        return Ok(());
+    }
+
+    let since = Utc::now() - Duration::minutes(i64::from(settings.window_minutes));
+    let (address_votes, network_votes) =
+        voter::Model::count_recent(&ctx.db, &addresses.address, since.into()).await?;
+
+    let assessment = risk::assess(
+        &settings,
+        &RiskSignals {
+            address_votes,
+            network_votes,
+            captcha_score,
+            ..RiskSignals::from_headers(headers)
+        },
+    );
+    debug!(
+        "Vote risk score {:.2} ({:?}): {:?}",
+        assessment.score, assessment.level, assessment.reasons
+    );
+
+    match assessment.level {
+        RiskLevel::Low => {}
+        RiskLevel::Medium => screening.challenge = true,
+        RiskLevel::High => match settings.high_action {
+            HighRiskAction::Reject => {
+                return Err(Error::CustomError(
+                    StatusCode::FORBIDDEN,
+                    ErrorDetail::new("VOTE_REJECTED", "Vote looks automated"),
+                ))
+            }
+            HighRiskAction::Shadow => screening.shadow = true,
+        },
+    }
+
+    Ok(())
+}
+
+/// Checks the token of the extra challenge, voters without one are asked to
+/// solve it
+pub(crate) async fn verify_challenge(
+    verifiers: &Verifiers,
+    address: &str,
+    challenge_token: Option<&str>,
+) -> Result<()> {
+    let token = challenge_token
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| {
+            Error::CustomError(
+                StatusCode::FORBIDDEN,
+                ErrorDetail::new(
+                    "CHALLENGE_REQUIRED",
+                    "Solve the extra challenge and vote again",
+                ),
+            )
+        })?;
+
+    let challenge = verifiers.challenge.as_ref().ok_or_else(|| {
+        error!("Extra challenge asked for without `settings.risk.challenge`");
+        captcha_error(CaptchaError::MissingSecret)
+    })?;
+    challenge
+        .verify(token, Some(address))
+        .await
+        .map_err(captcha_error)?;
+
+    Ok(())
+}
@@ -1,24 +1,52 @@
-use axum::http::{HeaderMap, StatusCode};
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Extension,
+};
 use axum_client_ip::SecureClientIp;
 use loco_rs::{controller::ErrorDetail, prelude::*};
 use serde::Serialize;
 use tracing::error;
 
-use crate::{models::_entities::user, utils::get_ip::get_ip};
+use super::{verified_identity, voter_addresses};
+use crate::{
+    models::{
+        _entities::{season, voter},
+        voter::VoteStatus,
+    },
+    utils::get_ip::{get_ip, ClientIpResolver},
+};
 
 #[derive(Serialize, Debug)]
 struct StatusResponse {
     voted_user: Option<String>,
+    status: Option<VoteStatus>,
 }
 
 pub async fn status(
     secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
     State(ctx): State<AppContext>,
     headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
-    let ip = get_ip(&secure_ip, &headers);
+    let ip = get_ip(&client_ip, &secure_ip, &headers);
+    // between seasons, the vote of the season that just ended is shown
+    let Some(season) = season::Model::find_latest(&ctx.db).await? else {
+        return Ok(Json(StatusResponse {
+            voted_user: None,
+            status: None,
+        }));
+    };
+    let identity = verified_identity(&ctx, &headers).await?;
+    let addresses = voter_addresses(&ctx, &ip, &headers, false).await?;
+    // voters that can't be recognized haven't voted yet
+    let Some(key) = addresses.voter_key(identity.as_ref()) else {
+        return Ok(Json(StatusResponse {
+            voted_user: None,
+            status: None,
+        }));
+    };
 
-    let voted_user = user::Model::find_voted_user_by_address(&ctx.db, &ip)
+    let vote = voter::Model::find_with_user(&ctx.db, key, season.id)
         .await
         .map_err(|err| {
             error!("Internal server error while getting status: {}", err);
@@ -29,7 +57,10 @@ pub async fn status(
             )
         })?;
 
-    Ok(Json(StatusResponse {
-        voted_user: voted_user.map(|u| u.username),
-    }))
+    let (voted_user, status) = match vote {
+        Some((voter, user)) => (user.map(|u| u.username), Some(voter.status())),
+        None => (None, None),
+    };
+
+    Ok(Json(StatusResponse { voted_user, status }))
 }
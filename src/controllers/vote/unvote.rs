@@ -1,44 +1,74 @@
-use axum::http::{HeaderMap, StatusCode};
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Extension,
+};
 use axum_client_ip::SecureClientIp;
 use loco_rs::{controller::ErrorDetail, prelude::*};
 use tracing::error;
 
+use super::{verified_identity, vote_invalidated, voter_addresses};
 use crate::{
-    models::{_entities::voter, voter::DeleteVoterError},
-    utils::get_ip::get_ip,
+    leaderboard_cache::LeaderboardCache,
+    models::{
+        _entities::{season, voter},
+        voter::DeleteVoterError,
+    },
+    utils::get_ip::{get_ip, ClientIpResolver},
 };
 
+fn voter_not_found() -> Error {
+    Error::CustomError(
+        StatusCode::NOT_FOUND,
+        ErrorDetail::new("NOT_FOUND", "Voter not found"),
+    )
+}
+
 pub async fn unvote(
     secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
     State(ctx): State<AppContext>,
     headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
-    let ip = get_ip(&secure_ip, &headers);
+    let ip = get_ip(&client_ip, &secure_ip, &headers);
+    // votes of past seasons can't be taken back
+    let season = season::Model::find_active(&ctx.db)
+        .await?
+        .ok_or_else(voter_not_found)?;
+    let identity = verified_identity(&ctx, &headers).await?;
+    let addresses = voter_addresses(&ctx, &ip, &headers, false).await?;
+    let key = addresses
+        .voter_key(identity.as_ref())
+        .ok_or_else(voter_not_found)?;
 
-    voter::Model::delete(&ctx.db, &ip).await.map_err(|err| {
-        let status_code;
-        let err_shorthand;
+    voter::Model::delete(&ctx.db, key, season.id)
+        .await
+        .map_err(|err| {
+            let status_code;
+            let err_shorthand;
 
-        match err {
-            DeleteVoterError::NotFound => {
-                status_code = StatusCode::NOT_FOUND;
-                err_shorthand = "NOT_FOUND";
+            match err {
+                DeleteVoterError::NotFound => {
+                    status_code = StatusCode::NOT_FOUND;
+                    err_shorthand = "NOT_FOUND";
+                }
+                DeleteVoterError::Invalidated => return vote_invalidated(),
+                _ => {
+                    error!("Error unvoting: {:?}", err);
+                    status_code = StatusCode::INTERNAL_SERVER_ERROR;
+                    err_shorthand = "INTERNAL_SERVER_ERROR";
+                }
             }
-            _ => {
-                error!("Error unvoting: {:?}", err);
-                status_code = StatusCode::INTERNAL_SERVER_ERROR;
-                err_shorthand = "INTERNAL_SERVER_ERROR";
-            }
-        }
-
-        Error::CustomError(
-            status_code,
-            ErrorDetail {
-                error: Some(err_shorthand.to_string()),
-                description: Some(err.to_string()),
-            },
-        )
-    })?;
+
+            Error::CustomError(
+                status_code,
+                ErrorDetail {
+                    error: Some(err_shorthand.to_string()),
+                    description: Some(err.to_string()),
+                },
+            )
+        })?;
+
+    LeaderboardCache::shared(&ctx).await?.invalidate();
 
     Ok(StatusCode::OK)
 }
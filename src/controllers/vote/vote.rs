@@ -1,77 +1,111 @@
-use axum::http::{HeaderMap, StatusCode};
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::AppendHeaders,
+    Extension,
+};
 use axum_client_ip::SecureClientIp;
-use loco_rs::{controller::ErrorDetail, prelude::*};
-use serde::Deserialize;
+use loco_rs::{controller::ErrorDetail, prelude::*, worker::AppWorker};
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
 use tracing::error;
 
+use super::{
+    active_season, captcha_error, check_ip_lists, check_risk, check_votable, verified_identity,
+    verify_challenge, vote_invalidated, voter_addresses,
+};
 use crate::{
-    app::REQWEST_CLIENT,
+    ip_reputation::IpReputation,
+    leaderboard_cache::LeaderboardCache,
     models::{
         _entities::{user, voter},
-        voter::VoterError,
+        voter::{VoteStatus, VoterError},
     },
-    utils::get_ip::get_ip,
+    utils::get_ip::{get_ip, ClientIpResolver},
+    verifiers::Verifiers,
+    workers::verify_vote::{VerifyVoteWorker, VerifyVoteWorkerArgs},
 };
 
 pub async fn vote(
     secure_ip: SecureClientIp,
+    Extension(client_ip): Extension<ClientIpResolver>,
     headers: HeaderMap,
     State(ctx): State<AppContext>,
+    Extension(verifiers): Extension<Verifiers>,
+    Extension(reputation): Extension<IpReputation>,
     Json(params): Json<VoteRequest>,
 ) -> Result<impl IntoResponse> {
     let username = &params.username.to_lowercase();
+    let address = get_ip(&client_ip, &secure_ip, &headers);
 
-    check_recaptcha_token(&params.recaptcha_token)
+    let captcha = verifiers
+        .captcha
+        .verify(&params.recaptcha_token, Some(&address))
         .await
-        .map_err(|err| {
-            let status_code;
-            let err_shorthand;
-
-            match &err {
-                CheckTokenError::GoogleNotWorking(e) => {
-                    error!("Google not working: {}", e);
-                    status_code = StatusCode::SERVICE_UNAVAILABLE;
-                    err_shorthand = "GOOGLE_NOT_WORKING";
-                }
-                CheckTokenError::FailedToParse(e) => {
-                    error!("Failed to parse ReCaptcha response: {}", e);
-                    status_code = StatusCode::INTERNAL_SERVER_ERROR;
-                    err_shorthand = "FAILED_TO_PARSE";
-                }
-                CheckTokenError::RecaptchaFailed => {
-                    status_code = StatusCode::FORBIDDEN;
-                    err_shorthand = "RECAPTCHA_FAILED";
-                }
-            };
-
-            Error::CustomError(
-                status_code,
-                ErrorDetail {
-                    error: Some(err_shorthand.to_string()),
-                    description: Some(err.to_string()),
-                },
-            )
-        })?;
-
-    check_username(username).await.map_err(|err| {
+        .map_err(captcha_error)?;
+
+    if username.is_empty() || username.len() > 30 {
+        return Err(Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("LENGTH_INVALID", "Username is too long/short"),
+        ));
+    }
+
+    let mut screening = check_ip_lists(&reputation, &address)?;
+
+    check_votable(&ctx, username).await?;
+
+    let season = active_season(&ctx).await?;
+    let identity = verified_identity(&ctx, &headers).await?;
+    // anonymous voters without a voter cookie get one
+    let addresses = voter_addresses(&ctx, &address, &headers, identity.is_none()).await?;
+    let key = addresses.voter_key(identity.as_ref()).ok_or_else(|| {
+        Error::CustomError(
+            StatusCode::BAD_REQUEST,
+            ErrorDetail::new("VOTER_UNRESOLVED", "Voter could not be recognized"),
+        )
+    })?;
+
+    check_risk(&ctx, &headers, &addresses, captcha.score, &mut screening).await?;
+    if screening.challenge {
+        verify_challenge(&verifiers, &address, params.challenge_token.as_deref()).await?;
+    }
+
+    let voted_user_id = user::Model::add(&ctx.db, username).await?.id;
+
+    let voter = voter::Model::add(
+        &ctx.db,
+        key,
+        season.id,
+        &addresses.address,
+        addresses.cap,
+        voted_user_id,
+        VoteStatus::Pending,
+        screening.screened(),
+    )
+    .await
+    .map_err(|err| {
         let status_code;
         let err_shorthand;
 
-        match &err {
-            UsernameCheckError::ThreadsNotWorking(e) => {
-                error!("Threads not working: {}", e);
-                status_code = StatusCode::SERVICE_UNAVAILABLE;
-                err_shorthand = "THREADS_NOT_WORKING";
+        match err {
+            VoterError::AlreadyVoted => {
+                status_code = StatusCode::CONFLICT;
+                err_shorthand = "ALREADY_VOTED";
             }
-            UsernameCheckError::UserNotFound => {
-                status_code = StatusCode::NOT_FOUND;
-                err_shorthand = "USER_NOT_FOUND";
+            VoterError::AddressCapReached => {
+                status_code = StatusCode::FORBIDDEN;
+                err_shorthand = "ADDRESS_CAP_REACHED";
             }
-            UsernameCheckError::LengthInvalid => {
-                status_code = StatusCode::BAD_REQUEST;
-                err_shorthand = "LENGTH_INVALID";
+            _ => {
+                error!(
+                    "Internal server error while adding voter to the db: {}",
+                    err
+                );
+
+                status_code = StatusCode::INTERNAL_SERVER_ERROR;
+                err_shorthand = "INTERNAL_ERROR";
             }
-        };
+        }
 
         Error::CustomError(
             status_code,
@@ -82,121 +116,69 @@ pub async fn vote(
         )
     })?;
 
-    let voted_user_id = user::Model::add(&ctx.db, username).await?.id;
-
-    let address = get_ip(&secure_ip, &headers);
-
-    voter::Model::add(&ctx.db, &address, voted_user_id)
-        .await
-        .map_err(|err| {
-            let status_code;
-            let err_shorthand;
-
-            match err {
-                VoterError::AlreadyVoted => {
-                    status_code = StatusCode::CONFLICT;
-                    err_shorthand = "ALREADY_VOTED";
-                }
-                _ => {
-                    error!(
-                        "Internal server error while adding voter to the db: {}",
-                        err
-                    );
-
-                    status_code = StatusCode::INTERNAL_SERVER_ERROR;
-                    err_shorthand = "INTERNAL_ERROR";
-                }
-            }
-
-            Error::CustomError(
-                status_code,
-                ErrorDetail {
-                    error: Some(err_shorthand.to_string()),
-                    description: Some(err.to_string()),
-                },
-            )
-        })?;
-
-    Ok(StatusCode::OK)
-}
-
-#[derive(Deserialize, Debug)]
-pub struct VoteRequest {
-    pub username: String,
-    pub recaptcha_token: String,
-}
-
-#[derive(thiserror::Error, Debug)]
-enum UsernameCheckError {
-    #[error("Threads not working")]
-    ThreadsNotWorking(#[from] reqwest::Error),
+    if let Err(err) =
+        VerifyVoteWorker::perform_later(&ctx, VerifyVoteWorkerArgs { voter_id: voter.id }).await
+    {
+        error!("Could not queue the vote verification: {}", err);
 
-    #[error("User not found")]
-    UserNotFound,
+        // a vote that is never verified would stay pending forever, the voter
+        // can try again instead
+        voter::Entity::delete_by_id(voter.id).exec(&ctx.db).await?;
 
-    #[error("Username is too long/short")]
-    LengthInvalid,
-}
-
-/// Checks if the username is valid and exists on threads
-async fn check_username(username: &String) -> std::result::Result<(), UsernameCheckError> {
-    if username.is_empty() || username.len() > 30 {
-        return Err(UsernameCheckError::LengthInvalid);
+        return Err(Error::CustomError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorDetail::new("VERIFICATION_UNAVAILABLE", "Vote could not be verified"),
+        ));
     }
-
-    let request = REQWEST_CLIENT
-        .client
-        .get(format!("https://threads.net/@{}", username))
-        .build()?;
-    let result = REQWEST_CLIENT.client.execute(request).await?.text().await?;
-
-    match result.find(username) {
-        Some(_) => {}
-        None => return Err(UsernameCheckError::UserNotFound),
+    LeaderboardCache::shared(&ctx).await?.invalidate();
+
+    // the worker may already be done, e.g. when workers run in the foreground.
+    // Votes for usernames that don't exist are deleted by it.
+    let status = voter::Entity::find_by_id(voter.id)
+        .one(&ctx.db)
+        .await?
+        .map_or(VoteStatus::NotFound, |voter| voter.status());
+
+    let set_cookie = AppendHeaders(
+        addresses
+            .set_cookie
+            .map(|cookie| (header::SET_COOKIE, cookie)),
+    );
+
+    match status {
+        VoteStatus::Confirmed => Ok((StatusCode::OK, set_cookie, Json(VoteResponse { status }))),
+        VoteStatus::Pending => Ok((
+            StatusCode::ACCEPTED,
+            set_cookie,
+            Json(VoteResponse { status }),
+        )),
+        VoteStatus::NotFound => Err(Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("USER_NOT_FOUND", "User not found"),
+        )),
+        VoteStatus::Failed => Err(Error::CustomError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorDetail::new("THREADS_NOT_WORKING", "Threads not working"),
+        )),
+        VoteStatus::Invalidated => Err(vote_invalidated()),
+        // unvoted before the verification was done
+        VoteStatus::Withdrawn => Err(Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("NOT_FOUND", "Voter not found"),
+        )),
     }
-
-    Ok(())
 }
 
-#[derive(thiserror::Error, Debug)]
-enum CheckTokenError {
-    #[error("Google not working")]
-    GoogleNotWorking(#[from] reqwest::Error),
-
-    #[error("Failed to parse recaptcha response")]
-    FailedToParse(#[from] serde_json::Error),
-
-    #[error("Recaptcha failed")]
-    RecaptchaFailed,
+#[derive(Serialize, Debug)]
+struct VoteResponse {
+    status: VoteStatus,
 }
 
 #[derive(Deserialize, Debug)]
-struct RecaptchaResponse {
-    success: bool,
-    _challenge_ts: Option<String>,
-    _hostname: Option<String>,
-    _error_codes: Option<Vec<String>>,
-}
-
-async fn check_recaptcha_token(token: &String) -> std::result::Result<(), CheckTokenError> {
-    let secret = std::env::var("RECAPTCHA_SECRET").unwrap();
-
-    let request = REQWEST_CLIENT
-        .client
-        .post(format!(
-            "https://www.google.com/recaptcha/api/siteverify?response={}&secret={}",
-            token, secret
-        ))
-        .header("Content-Length", "0")
-        .build()?;
-
-    let result = REQWEST_CLIENT.client.execute(request).await?.text().await?;
-
-    let result: RecaptchaResponse = serde_json::from_str(&result)?;
-
-    if !result.success {
-        return Err(CheckTokenError::RecaptchaFailed);
-    }
-
-    Ok(())
+pub struct VoteRequest {
+    pub username: String,
+    #[serde(alias = "captcha_token")]
+    pub recaptcha_token: String,
+    /// Token of the extra challenge, needed when the vote is scored as risky
+    pub challenge_token: Option<String>,
 }
@@ -1,7 +1,9 @@
-use axum::{async_trait, Router as AxumRouter};
+use axum::{async_trait, Extension, Router as AxumRouter};
 use axum_client_ip::SecureClientIpSource;
 use loco_rs::prelude::*;
 
+use crate::utils::get_ip::ClientIpResolver;
+
 pub struct IPGetterInitializer;
 
 #[async_trait]
@@ -10,8 +12,10 @@ impl Initializer for IPGetterInitializer {
         "ip_getter".to_string()
     }
 
-    async fn after_routes(&self, router: AxumRouter, _ctx: &AppContext) -> Result<AxumRouter> {
-        let app = router.layer(SecureClientIpSource::ConnectInfo.into_extension());
+    async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+        let app = router
+            .layer(SecureClientIpSource::ConnectInfo.into_extension())
+            .layer(Extension(ClientIpResolver::from_context(ctx)?));
 
         Ok(app)
     }
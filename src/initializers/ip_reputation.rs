@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use axum::{async_trait, Extension, Router as AxumRouter};
+use loco_rs::prelude::*;
+use tracing::{error, info};
+
+use crate::{common::settings::Settings, ip_reputation::IpReputation};
+
+pub struct IpReputationInitializer;
+
+#[async_trait]
+impl Initializer for IpReputationInitializer {
+    fn name(&self) -> String {
+        "ip_reputation".to_string()
+    }
+
+    async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+        let settings = Settings::from_context(ctx)?.ip_reputation;
+        let reputation = IpReputation::from_settings(&settings)
+            .map_err(|err| Error::Message(format!("could not load IP lists: {}", err)))?;
+
+        if !settings.lists.is_empty() && settings.reload_interval > 0 {
+            let reputation = reputation.clone();
+            let mut interval = tokio::time::interval(Duration::from_secs(settings.reload_interval));
+
+            tokio::spawn(async move {
+                // the first tick completes right away, the lists were just loaded
+                interval.tick().await;
+
+                loop {
+                    interval.tick().await;
+
+                    let reputation = reputation.clone();
+                    match tokio::task::spawn_blocking(move || reputation.reload()).await {
+                        Ok(0) => {}
+                        Ok(reloaded) => info!("Reloaded {} IP lists", reloaded),
+                        Err(err) => error!("Could not reload the IP lists: {}", err),
+                    }
+                }
+            });
+        }
+
+        let app = router.layer(Extension(reputation));
+
+        Ok(app)
+    }
+}
@@ -1 +1,4 @@
 pub mod ip_getter;
+pub mod ip_reputation;
+pub mod rate_limit;
+pub mod verifiers;
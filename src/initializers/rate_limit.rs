@@ -0,0 +1,121 @@
+use std::net::SocketAddr;
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Json, Router as AxumRouter,
+};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use tracing::{error, warn};
+
+use crate::{
+    common::settings::Settings,
+    rate_limit::RateLimiter,
+    utils::{address_hash::AddressHasher, get_ip::ClientIpResolver},
+};
+
+/// Routes that spend captcha or Threads quota, they should always have a rule
+const PROTECTED_ROUTES: [(&str, &str); 3] = [
+    ("POST", "/api/vote"),
+    ("POST", "/api/reports"),
+    ("POST", "/api/identity/claim"),
+];
+
+pub struct RateLimitInitializer;
+
+#[derive(Clone)]
+struct RateLimitState {
+    limiter: RateLimiter,
+    client_ip: ClientIpResolver,
+    hasher: AddressHasher,
+}
+
+#[async_trait]
+impl Initializer for RateLimitInitializer {
+    fn name(&self) -> String {
+        "rate_limit".to_string()
+    }
+
+    async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+        let settings = Settings::from_context(ctx)?;
+        if !settings.rate_limit.enable {
+            return Ok(router);
+        }
+
+        let state = RateLimitState {
+            limiter: RateLimiter::from_settings(&settings.rate_limit, &ctx.db)?,
+            client_ip: ClientIpResolver::new(&settings.client_ip),
+            hasher: AddressHasher::from_settings(&settings)?,
+        };
+
+        for (method, path) in PROTECTED_ROUTES {
+            if state.limiter.rule(method, path).is_none() {
+                warn!("No rate limit rule for {} {}", method, path);
+            }
+        }
+
+        let app = router.layer(middleware::from_fn_with_state(state, rate_limit));
+
+        Ok(app)
+    }
+}
+
+async fn rate_limit(State(state): State<RateLimitState>, request: Request, next: Next) -> Response {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+    let Some(rule) = state.limiter.rule(request.method().as_str(), path).cloned() else {
+        return next.run(request).await;
+    };
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        return next.run(request).await;
+    };
+
+    let client = state
+        .hasher
+        .hash(
+            &state
+                .client_ip
+                .resolve(peer.ip(), request.headers())
+                .to_string(),
+        )
+        .hash;
+
+    match state.limiter.check(&rule, &client).await {
+        Ok(None) => next.run(request).await,
+        Ok(Some(wait)) => {
+            let retry_after = (wait.as_secs_f64().ceil() as u64).max(1);
+
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(ErrorDetail::new(
+                    "TOO_MANY_REQUESTS",
+                    "Too many requests, try again later",
+                )),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            error!("Rate limiter not working: {}", err);
+
+            if state.limiter.fail_closed() {
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ErrorDetail::new(
+                        "RATE_LIMIT_UNAVAILABLE",
+                        "Try again later",
+                    )),
+                )
+                    .into_response();
+            }
+
+            // better to let a request through than to take the site down
+            next.run(request).await
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{async_trait, Extension, Router as AxumRouter};
+use loco_rs::prelude::*;
+
+use crate::verifiers::Verifiers;
+
+pub struct VerifiersInitializer;
+
+#[async_trait]
+impl Initializer for VerifiersInitializer {
+    fn name(&self) -> String {
+        "verifiers".to_string()
+    }
+
+    async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
+        let verifiers = Verifiers::shared(ctx).await?;
+
+        let app = router.layer(Extension(verifiers));
+
+        Ok(app)
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fs,
+    net::IpAddr,
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use ipnet::IpNet;
+use tracing::{info, warn};
+
+use self::trie::PrefixTrie;
+use crate::common::settings::{IpListPolicy, IpListSettings, IpReputationSettings};
+
+pub mod trie;
+
+#[derive(thiserror::Error, Debug)]
+pub enum IpListError {
+    #[error("could not read {0}: {1}")]
+    Read(String, std::io::Error),
+}
+
+/// A list the address is on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListMatch {
+    pub name: String,
+    pub policy: IpListPolicy,
+}
+
+struct LoadedList {
+    settings: IpListSettings,
+    /// Modification time of the file when it was read
+    modified: Option<SystemTime>,
+    networks: PrefixTrie,
+}
+
+/// The address reputation lists, shared with the handlers and swapped out
+/// when their files change
+#[derive(Clone, Default)]
+pub struct IpReputation {
+    lists: Arc<RwLock<Vec<LoadedList>>>,
+}
+
+impl IpReputation {
+    /// Loads every list, a list that can't be read is a configuration error
+    pub fn from_settings(settings: &IpReputationSettings) -> Result<Self, IpListError> {
+        let lists = settings
+            .lists
+            .iter()
+            .map(|list| load(list.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            lists: Arc::new(RwLock::new(lists)),
+        })
+    }
+
+    /// Whether no list is configured
+    pub fn is_empty(&self) -> bool {
+        self.lists
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Finds the lists the address is on
+    pub fn lookup(&self, ip: IpAddr) -> Vec<ListMatch> {
+        self.lists
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|list| list.networks.contains(ip))
+            .map(|list| ListMatch {
+                name: list.settings.name.clone(),
+                policy: list.settings.policy,
+            })
+            .collect()
+    }
+
+    /// Reloads the lists whose file changed since it was read. A list whose
+    /// file can't be read keeps its networks. Returns how many were reloaded.
+    pub fn reload(&self) -> usize {
+        let changed: Vec<(usize, IpListSettings)> = self
+            .lists
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .enumerate()
+            .filter(|(_, list)| modified(&list.settings.path) != list.modified)
+            .map(|(index, list)| (index, list.settings.clone()))
+            .collect();
+
+        // the files are read without holding the lock, lookups go on with
+        // the previous networks meanwhile
+        let mut reloaded = Vec::new();
+        for (index, settings) in changed {
+            match load(settings) {
+                Ok(list) => reloaded.push((index, list)),
+                Err(err) => warn!("Keeping the previous IP list: {}", err),
+            }
+        }
+
+        let count = reloaded.len();
+        let mut lists = self.lists.write().unwrap_or_else(PoisonError::into_inner);
+        for (index, list) in reloaded {
+            lists[index] = list;
+        }
+
+        count
+    }
+}
+
+/// Parses one address or CIDR per line, `#` starts a comment. Returns the
+/// networks and how many lines couldn't be parsed.
+pub fn parse_list(content: &str) -> (PrefixTrie, usize) {
+    let mut networks = PrefixTrie::new();
+    let mut invalid = 0;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match line
+            .parse::<IpNet>()
+            .or_else(|_| line.parse::<IpAddr>().map(IpNet::from))
+        {
+            Ok(network) => networks.insert(network),
+            Err(_) => invalid += 1,
+        }
+    }
+
+    (networks, invalid)
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn load(settings: IpListSettings) -> Result<LoadedList, IpListError> {
+    // taken before reading, so a change while reading is picked up next time
+    let modified = modified(&settings.path);
+    let content = fs::read_to_string(&settings.path)
+        .map_err(|err| IpListError::Read(settings.path.clone(), err))?;
+
+    let (networks, invalid) = parse_list(&content);
+    if invalid > 0 {
+        warn!("Skipped {} invalid lines of {}", invalid, settings.path);
+    }
+    info!(
+        "Loaded {} networks of the {} IP list",
+        networks.len(),
+        settings.name
+    );
+
+    Ok(LoadedList {
+        settings,
+        modified,
+        networks,
+    })
+}
@@ -0,0 +1,198 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+/// Index of the root node, no node points back to it so it doubles as "no
+/// child"
+const ROOT: u32 = 0;
+
+#[derive(Clone, Copy, Default)]
+struct Node {
+    children: [u32; 2],
+    /// A network ends here, every address below it is in the set
+    terminal: bool,
+}
+
+/// Prefix length of the IPv4-mapped IPv6 addresses, `::ffff:0:0/96`
+const V4_MAPPED_PREFIX: u8 = 96;
+
+/// One tree of nodes, nodes cut off when a shorter network covers them are
+/// reused by later inserts
+#[derive(Clone)]
+struct Tree {
+    nodes: Vec<Node>,
+    free: Vec<u32>,
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+            free: Vec::new(),
+        }
+    }
+}
+
+impl Tree {
+    fn add(&mut self) -> u32 {
+        match self.free.pop() {
+            Some(node) => {
+                self.nodes[node as usize] = Node::default();
+                node
+            }
+            None => {
+                self.nodes.push(Node::default());
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Frees the nodes below `node`, returns how many networks ended there
+    fn cut(&mut self, node: usize) -> usize {
+        let mut networks = 0;
+        let mut below: Vec<u32> = self.nodes[node].children.to_vec();
+        self.nodes[node].children = [ROOT; 2];
+
+        while let Some(child) = below.pop() {
+            if child == ROOT {
+                continue;
+            }
+            let child_node = self.nodes[child as usize];
+            if child_node.terminal {
+                networks += 1;
+            }
+            below.extend(child_node.children);
+            self.free.push(child);
+        }
+
+        networks
+    }
+}
+
+/// A set of networks, looked up by walking the bits of the address. IPv4
+/// and IPv6 networks are kept in separate trees, IPv4-mapped IPv6 networks
+/// go in the IPv4 one.
+#[derive(Clone, Default)]
+pub struct PrefixTrie {
+    v4: Tree,
+    v6: Tree,
+    len: usize,
+}
+
+impl PrefixTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many networks the set holds, networks covered by another one
+    /// aren't counted
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Nodes in use, the memory the set takes grows with them
+    pub fn node_count(&self) -> usize {
+        self.v4.nodes.len() - self.v4.free.len() + self.v6.nodes.len() - self.v6.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, network: IpNet) {
+        let network = network.trunc();
+
+        if let IpNet::V6(v6) = network {
+            // looked up as IPv4, see `contains`
+            if let Some(v4) = to_v4(v6) {
+                return self.insert(IpNet::V4(v4));
+            }
+            // a shorter network spanning the mapped addresses spans IPv4
+            if v6.contains(&v4_mapped()) {
+                self.insert(IpNet::V4(Ipv4Net::default()));
+            }
+        }
+
+        let (tree, bits) = match network {
+            IpNet::V4(network) => (&mut self.v4, u128::from(u32::from(network.addr())) << 96),
+            IpNet::V6(network) => (&mut self.v6, u128::from(network.addr())),
+        };
+
+        let mut node = ROOT as usize;
+        for depth in 0..network.prefix_len() {
+            // networks already covered by a shorter one add nothing
+            if tree.nodes[node].terminal {
+                return;
+            }
+
+            let bit = bit(bits, depth);
+            if tree.nodes[node].children[bit] == ROOT {
+                let child = tree.add();
+                tree.nodes[node].children[bit] = child;
+            }
+            node = tree.nodes[node].children[bit] as usize;
+        }
+
+        if !tree.nodes[node].terminal {
+            tree.nodes[node].terminal = true;
+            // the longer networks below are covered now
+            let covered = tree.cut(node);
+            self.len = self.len + 1 - covered;
+        }
+    }
+
+    /// Whether the address is in any of the networks
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (nodes, bits, len) = match ip.to_canonical() {
+            IpAddr::V4(ip) => (&self.v4.nodes, u128::from(u32::from(ip)) << 96, 32),
+            IpAddr::V6(ip) => (&self.v6.nodes, u128::from(ip), 128),
+        };
+
+        let mut node = ROOT as usize;
+        for depth in 0..len {
+            if nodes[node].terminal {
+                return true;
+            }
+
+            match nodes[node].children[bit(bits, depth)] {
+                ROOT => return false,
+                child => node = child as usize,
+            }
+        }
+
+        nodes[node].terminal
+    }
+}
+
+impl FromIterator<IpNet> for PrefixTrie {
+    fn from_iter<T: IntoIterator<Item = IpNet>>(networks: T) -> Self {
+        let mut trie = Self::new();
+        for network in networks {
+            trie.insert(network);
+        }
+
+        trie
+    }
+}
+
+/// `::ffff:0:0/96`, where IPv4 addresses are mapped into IPv6
+fn v4_mapped() -> Ipv6Net {
+    Ipv6Net::new(Ipv4Addr::UNSPECIFIED.to_ipv6_mapped(), V4_MAPPED_PREFIX)
+        .expect("96 is a valid IPv6 prefix")
+}
+
+/// The IPv4 network of an IPv4-mapped IPv6 network
+fn to_v4(network: Ipv6Net) -> Option<Ipv4Net> {
+    if network.prefix_len() < V4_MAPPED_PREFIX {
+        return None;
+    }
+    let addr: Ipv6Addr = network.addr();
+
+    addr.to_ipv4_mapped()
+        .and_then(|addr| Ipv4Net::new(addr, network.prefix_len() - V4_MAPPED_PREFIX).ok())
+}
+
+/// The bit at `depth` from the left of an address aligned to 128 bits
+fn bit(bits: u128, depth: u8) -> usize {
+    ((bits >> (127 - depth)) & 1) as usize
+}
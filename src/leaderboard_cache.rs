@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use loco_rs::prelude::*;
+use tokio::sync::OnceCell;
+
+use crate::{
+    common::settings::{LeaderboardCacheSettings, Settings},
+    models::user::LeaderboardWindow,
+    views::leaderboard::LeaderboardResponse,
+};
+
+static SHARED: OnceCell<LeaderboardCache> = OnceCell::const_new();
+
+/// What a leaderboard page is cached on, as requested
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeaderboardKey {
+    /// `None` for the latest season
+    pub season: Option<i32>,
+    pub username: Option<String>,
+    pub window: LeaderboardWindow,
+    pub page: u64,
+}
+
+struct Entry {
+    response: Arc<LeaderboardResponse>,
+    /// Generation of the cache when the page was read from the database
+    generation: u64,
+    fetched_at: Instant,
+}
+
+/// Leaderboard pages with their pagination, kept in process. Writes bump the
+/// generation, which makes every page stale without dropping it, so it can
+/// still be served while the database is down.
+pub struct LeaderboardCache {
+    settings: LeaderboardCacheSettings,
+    generation: AtomicU64,
+    entries: Mutex<HashMap<LeaderboardKey, Entry>>,
+}
+
+impl LeaderboardCache {
+    pub fn new(settings: &LeaderboardCacheSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            generation: AtomicU64::new(0),
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Returns the cache of this process, building it on first use
+    pub async fn shared(ctx: &AppContext) -> Result<&'static Self> {
+        SHARED
+            .get_or_try_init(|| async {
+                Ok(Self::new(&Settings::from_context(ctx)?.leaderboard_cache))
+            })
+            .await
+    }
+
+    /// Taken before reading a page from the database, so a write made while
+    /// reading invalidates the page right away
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Gets the page when no write happened since it was read and it is
+    /// younger than the ttl
+    pub fn get(&self, key: &LeaderboardKey) -> Option<Arc<LeaderboardResponse>> {
+        if !self.settings.enable {
+            return None;
+        }
+
+        let generation = self.generation();
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        entries
+            .get(key)
+            .filter(|entry| {
+                entry.generation == generation
+                    && entry.fetched_at.elapsed() < Duration::from_secs(self.settings.ttl)
+            })
+            .map(|entry| entry.response.clone())
+    }
+
+    /// Gets the page, however outdated, when it is younger than the stale
+    /// ttl, with how long ago it was read
+    pub fn get_stale(&self, key: &LeaderboardKey) -> Option<(Arc<LeaderboardResponse>, Duration)> {
+        if !self.settings.enable {
+            return None;
+        }
+
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        entries
+            .get(key)
+            .map(|entry| (entry.response.clone(), entry.fetched_at.elapsed()))
+            .filter(|(_, age)| *age < Duration::from_secs(self.settings.stale_ttl))
+    }
+
+    pub fn insert(&self, key: LeaderboardKey, generation: u64, response: Arc<LeaderboardResponse>) {
+        if !self.settings.enable {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if entries.len() >= self.settings.max_entries && !entries.contains_key(&key) {
+            let stale_ttl = Duration::from_secs(self.settings.stale_ttl);
+            entries.retain(|_, entry| entry.fetched_at.elapsed() < stale_ttl);
+
+            // still full of fresh pages, the oldest one makes room
+            if entries.len() >= self.settings.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.fetched_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(
+            key,
+            Entry {
+                response,
+                generation,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Called after a vote changed, cached pages are only served stale from
+    /// now on
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
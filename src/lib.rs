@@ -1,7 +1,15 @@
+pub mod anomaly;
 pub mod app;
 pub mod common;
 pub mod controllers;
 pub mod initializers;
+pub mod ip_reputation;
+pub mod leaderboard_cache;
 pub mod models;
+pub mod rate_limit;
+pub mod risk;
+pub mod tasks;
 pub mod utils;
+pub mod verifiers;
 pub mod views;
+pub mod workers;
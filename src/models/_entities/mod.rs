@@ -2,5 +2,13 @@
 
 pub mod prelude;
 
+pub mod blocked_username;
+pub mod crush_match;
+pub mod rate_limit_bucket;
+pub mod report;
+pub mod season;
 pub mod user;
+pub mod username_verification;
+pub mod vote_flag;
 pub mod voter;
+pub mod voter_identity;
@@ -1,3 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.14
 
-pub use super::{user::Entity as User, voter::Entity as Voter};
+pub use super::{
+    blocked_username::Entity as BlockedUsername, crush_match::Entity as CrushMatch,
+    rate_limit_bucket::Entity as RateLimitBucket, report::Entity as Report,
+    season::Entity as Season, user::Entity as User,
+    username_verification::Entity as UsernameVerification, vote_flag::Entity as VoteFlag,
+    voter::Entity as Voter, voter_identity::Entity as VoterIdentity,
+};
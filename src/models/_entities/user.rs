@@ -9,14 +9,30 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub username: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    pub owner_identity_id: Option<i32>,
+    pub claimed_at: Option<DateTimeWithTimeZone>,
+    pub hidden: bool,
+    pub hide_count: bool,
+    pub votes_closed: bool,
+    pub suspended: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::report::Entity")]
+    Report,
     #[sea_orm(has_many = "super::voter::Entity")]
     Voter,
 }
 
+impl Related<super::report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Report.def()
+    }
+}
+
 impl Related<super::voter::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Voter.def()
@@ -10,10 +10,26 @@ pub struct Model {
     pub id: i32,
     pub address: String,
     pub voted_user_id: i32,
+    pub created_at: DateTimeWithTimeZone,
+    pub status: String,
+    pub identity_id: Option<i32>,
+    pub updated_at: DateTimeWithTimeZone,
+    pub season_id: Option<i32>,
+    pub network: Option<String>,
+    pub ip_hash: Option<String>,
+    pub shadow: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::season::Entity",
+        from = "Column::SeasonId",
+        to = "super::season::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Season,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::VotedUserId",
@@ -22,6 +38,22 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(has_many = "super::vote_flag::Entity")]
+    VoteFlag,
+    #[sea_orm(
+        belongs_to = "super::voter_identity::Entity",
+        from = "Column::IdentityId",
+        to = "super::voter_identity::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    VoterIdentity,
+}
+
+impl Related<super::season::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Season.def()
+    }
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -29,3 +61,15 @@ impl Related<super::user::Entity> for Entity {
         Relation::User.def()
     }
 }
+
+impl Related<super::vote_flag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VoteFlag.def()
+    }
+}
+
+impl Related<super::voter_identity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VoterIdentity.def()
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::Utc;
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, QueryOrder, TransactionTrait};
+
+use super::_entities::blocked_username::{self, ActiveModel};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert && self.created_at.is_not_set() {
+            self.created_at = ActiveValue::set(Utc::now().into());
+        }
+
+        Ok(self)
+    }
+}
+
+impl super::_entities::blocked_username::Model {
+    /// Blocked usernames can't be voted for
+    pub async fn is_blocked(db: &DatabaseConnection, username: &str) -> ModelResult<bool> {
+        let blocked = blocked_username::Entity::find()
+            .filter(blocked_username::Column::Username.eq(username))
+            .one(db)
+            .await?;
+
+        Ok(blocked.is_some())
+    }
+
+    pub async fn list(db: &DatabaseConnection) -> ModelResult<Vec<Self>> {
+        let blocked = blocked_username::Entity::find()
+            .order_by_asc(blocked_username::Column::Username)
+            .all(db)
+            .await?;
+
+        Ok(blocked)
+    }
+
+    /// Adds a username to the blocklist, only updating the reason when it is
+    /// already blocked
+    pub async fn block(
+        db: &DatabaseConnection,
+        username: &str,
+        reason: Option<String>,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+
+        let existing = blocked_username::Entity::find()
+            .filter(blocked_username::Column::Username.eq(username))
+            .one(&txn)
+            .await?;
+
+        let blocked = match existing {
+            Some(existing) => {
+                let mut blocked: ActiveModel = existing.into();
+                blocked.reason = ActiveValue::set(reason);
+                blocked.update(&txn).await?
+            }
+            None => {
+                blocked_username::ActiveModel {
+                    username: ActiveValue::set(username.to_string()),
+                    reason: ActiveValue::set(reason),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?
+            }
+        };
+
+        txn.commit().await?;
+
+        Ok(blocked)
+    }
+
+    /// Removes a username from the blocklist
+    ///
+    /// # Errors
+    ///
+    /// When the username isn't blocked or DB query error
+    pub async fn unblock(db: &DatabaseConnection, username: &str) -> ModelResult<()> {
+        let deleted = blocked_username::Entity::delete_many()
+            .filter(blocked_username::Column::Username.eq(username))
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        if deleted == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, ActiveValue, Condition};
+
+use super::{
+    _entities::{
+        crush_match::{self, ActiveModel},
+        user, voter, voter_identity,
+    },
+    voter::VoteStatus,
+};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl super::_entities::crush_match::Model {
+    /// Records a match when the confirmed vote of a verified voter is returned
+    /// by the verified voter it is for, in the season of the vote
+    pub async fn detect(
+        db: &DatabaseConnection,
+        voter: &voter::Model,
+    ) -> ModelResult<Option<Self>> {
+        let Some(identity_id) = voter.identity_id else {
+            return Ok(None);
+        };
+
+        if voter.status() != VoteStatus::Confirmed {
+            return Ok(None);
+        }
+
+        let Some(identity) = voter_identity::Entity::find_by_id(identity_id)
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(crush) = user::Entity::find_by_id(voter.voted_user_id)
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let Some(crush_identity) = voter_identity::Entity::find()
+            .filter(voter_identity::Column::Username.eq(&crush.username))
+            .filter(voter_identity::Column::VerifiedAt.is_not_null())
+            .one(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let returned = voter::Entity::find()
+            .inner_join(user::Entity)
+            .filter(voter::Column::SeasonId.eq(voter.season_id))
+            .filter(voter::Column::IdentityId.eq(crush_identity.id))
+            .filter(voter::Column::Status.eq(VoteStatus::Confirmed.as_str()))
+            .filter(user::Column::Username.eq(&identity.username))
+            .one(db)
+            .await?
+            .is_some();
+
+        if !returned {
+            return Ok(None);
+        }
+
+        // stored in a fixed order so each pair only matches once
+        let (identity_a_id, identity_b_id) = if identity.id < crush_identity.id {
+            (identity.id, crush_identity.id)
+        } else {
+            (crush_identity.id, identity.id)
+        };
+
+        crush_match::Entity::insert(crush_match::ActiveModel {
+            identity_a_id: ActiveValue::set(identity_a_id),
+            identity_b_id: ActiveValue::set(identity_b_id),
+            season_id: ActiveValue::set(voter.season_id),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([
+                crush_match::Column::SeasonId,
+                crush_match::Column::IdentityAId,
+                crush_match::Column::IdentityBId,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        let crush_match = crush_match::Entity::find()
+            .filter(crush_match::Column::SeasonId.eq(voter.season_id))
+            .filter(crush_match::Column::IdentityAId.eq(identity_a_id))
+            .filter(crush_match::Column::IdentityBId.eq(identity_b_id))
+            .one(db)
+            .await?;
+
+        Ok(crush_match)
+    }
+
+    /// finds the matches of an identity in a season together with the other
+    /// identity
+    pub async fn find_for_identity(
+        db: &DatabaseConnection,
+        identity_id: i32,
+        season_id: i32,
+    ) -> ModelResult<Vec<(Self, voter_identity::Model)>> {
+        let matches = crush_match::Entity::find()
+            .filter(crush_match::Column::SeasonId.eq(season_id))
+            .filter(
+                Condition::any()
+                    .add(crush_match::Column::IdentityAId.eq(identity_id))
+                    .add(crush_match::Column::IdentityBId.eq(identity_id)),
+            )
+            .all(db)
+            .await?;
+
+        let mut result = Vec::with_capacity(matches.len());
+        for crush_match in matches {
+            let other_id = if crush_match.identity_a_id == identity_id {
+                crush_match.identity_b_id
+            } else {
+                crush_match.identity_a_id
+            };
+
+            if let Some(other) = voter_identity::Entity::find_by_id(other_id).one(db).await? {
+                result.push((crush_match, other));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Deletes the matches of the identities in a season, or in every season
+    /// when `season_id` is `None`. Used when their votes go away.
+    pub async fn delete_for_identities<C>(
+        db: &C,
+        identity_ids: &[i32],
+        season_id: Option<i32>,
+    ) -> ModelResult<u64>
+    where
+        C: ConnectionTrait,
+    {
+        if identity_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query = crush_match::Entity::delete_many();
+        if let Some(season_id) = season_id {
+            query = query.filter(crush_match::Column::SeasonId.eq(season_id));
+        }
+
+        let deleted = query
+            .filter(
+                Condition::any()
+                    .add(crush_match::Column::IdentityAId.is_in(identity_ids.iter().copied()))
+                    .add(crush_match::Column::IdentityBId.is_in(identity_ids.iter().copied())),
+            )
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(deleted)
+    }
+}
@@ -1,3 +1,11 @@
 pub mod _entities;
+pub mod blocked_username;
+pub mod crush_match;
+pub mod rate_limit_bucket;
+pub mod report;
+pub mod season;
 pub mod user;
+pub mod username_verification;
+pub mod vote_flag;
 pub mod voter;
+pub mod voter_identity;
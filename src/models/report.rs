@@ -0,0 +1,239 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, QueryOrder, TransactionTrait};
+use serde::{Deserialize, Serialize};
+
+use super::_entities::{
+    report::{self, ActiveModel},
+    user,
+};
+use crate::views::leaderboard::Pagination;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert && self.created_at.is_not_set() {
+            self.created_at = ActiveValue::set(Utc::now().into());
+        }
+
+        Ok(self)
+    }
+}
+
+/// Moderation state of a report, stored in `report.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    /// Waiting in the moderation queue
+    Open,
+    /// A moderator acted on it
+    Resolved,
+    /// A moderator found nothing wrong
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "open" => Some(Self::Open),
+            "resolved" => Some(Self::Resolved),
+            "dismissed" => Some(Self::Dismissed),
+            _ => None,
+        }
+    }
+}
+
+/// What a moderator does about a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportAction {
+    /// Closes the report without touching the user
+    Dismiss,
+    /// Closes the report, the user was dealt with otherwise
+    Resolve,
+    /// Suspends the user and closes every open report against it
+    Suspend,
+}
+
+impl ReportAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dismiss => "dismiss",
+            Self::Resolve => "resolve",
+            Self::Suspend => "suspend",
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FileReportError {
+    #[error("User is already reported")]
+    AlreadyReported,
+
+    #[error(transparent)]
+    ModelError(#[from] ModelError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResolveReportError {
+    #[error("Report not found")]
+    NotFound,
+
+    #[error("Report is already closed")]
+    AlreadyClosed,
+
+    #[error(transparent)]
+    ModelError(#[from] ModelError),
+}
+
+impl super::_entities::report::Model {
+    pub fn status(&self) -> ReportStatus {
+        ReportStatus::parse(&self.status).unwrap_or(ReportStatus::Open)
+    }
+
+    /// Files a report against a user. A reporter has one open report per
+    /// user, filing another one is refused so its reason isn't lost.
+    pub async fn file(
+        db: &DatabaseConnection,
+        user_id: i32,
+        reason: &str,
+        reporter_hash: &str,
+    ) -> Result<Self, FileReportError> {
+        let txn = db.begin().await.map_err(ModelError::from)?;
+
+        let existing = report::Entity::find()
+            .filter(report::Column::UserId.eq(user_id))
+            .filter(report::Column::ReporterHash.eq(reporter_hash))
+            .filter(report::Column::Status.eq(ReportStatus::Open.as_str()))
+            .one(&txn)
+            .await
+            .map_err(ModelError::from)?;
+        if existing.is_some() {
+            return Err(FileReportError::AlreadyReported);
+        }
+
+        let report = report::ActiveModel {
+            user_id: ActiveValue::set(user_id),
+            reason: ActiveValue::set(reason.to_string()),
+            status: ActiveValue::set(ReportStatus::Open.as_str().to_string()),
+            reporter_hash: ActiveValue::set(Some(reporter_hash.to_string())),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(ModelError::from)?;
+
+        txn.commit().await.map_err(ModelError::from)?;
+
+        Ok(report)
+    }
+
+    /// Lists the reports with the reported user, oldest first so the queue is
+    /// worked through in order
+    pub async fn list(
+        db: &DatabaseConnection,
+        status: Option<ReportStatus>,
+        page: u64,
+        page_size: u64,
+    ) -> ModelResult<(Vec<(Self, Option<user::Model>)>, Pagination)> {
+        let mut select = report::Entity::find()
+            .find_also_related(user::Entity)
+            .order_by_asc(report::Column::CreatedAt)
+            .order_by_asc(report::Column::Id);
+
+        if let Some(status) = status {
+            select = select.filter(report::Column::Status.eq(status.as_str()));
+        }
+
+        let paginator = select.paginate(db, page_size);
+        let counts = paginator.num_items_and_pages().await?;
+        let reports = paginator.fetch_page(page.saturating_sub(1)).await?;
+
+        Ok((
+            reports,
+            Pagination {
+                current: page,
+                last: counts.number_of_pages,
+                entries: counts.number_of_items,
+            },
+        ))
+    }
+
+    /// Closes an open report. Suspending the user closes every open report
+    /// against it.
+    pub async fn resolve(
+        db: &DatabaseConnection,
+        id: i32,
+        action: ReportAction,
+        note: Option<String>,
+    ) -> Result<Self, ResolveReportError> {
+        let txn = db.begin().await.map_err(ModelError::from)?;
+
+        let report = report::Entity::find_by_id(id)
+            .one(&txn)
+            .await
+            .map_err(ModelError::from)?
+            .ok_or(ResolveReportError::NotFound)?;
+
+        if report.status() != ReportStatus::Open {
+            return Err(ResolveReportError::AlreadyClosed);
+        }
+
+        let now: DateTime<FixedOffset> = Utc::now().into();
+        let status = match action {
+            ReportAction::Dismiss => ReportStatus::Dismissed,
+            ReportAction::Resolve | ReportAction::Suspend => ReportStatus::Resolved,
+        };
+
+        if action == ReportAction::Suspend {
+            user::ActiveModel {
+                id: ActiveValue::unchanged(report.user_id),
+                suspended: ActiveValue::set(true),
+                ..Default::default()
+            }
+            .update(&txn)
+            .await
+            .map_err(ModelError::from)?;
+
+            report::Entity::update_many()
+                .col_expr(report::Column::Status, Expr::value(status.as_str()))
+                .col_expr(report::Column::Resolution, Expr::value(action.as_str()))
+                .col_expr(report::Column::ResolvedAt, Expr::value(now))
+                .filter(report::Column::UserId.eq(report.user_id))
+                .filter(report::Column::Status.eq(ReportStatus::Open.as_str()))
+                .filter(report::Column::Id.ne(report.id))
+                .exec(&txn)
+                .await
+                .map_err(ModelError::from)?;
+        }
+
+        let report = report::ActiveModel {
+            id: ActiveValue::unchanged(report.id),
+            status: ActiveValue::set(status.as_str().to_string()),
+            resolution: ActiveValue::set(Some(action.as_str().to_string())),
+            note: ActiveValue::set(note),
+            resolved_at: ActiveValue::set(Some(now)),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await
+        .map_err(ModelError::from)?;
+
+        txn.commit().await.map_err(ModelError::from)?;
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, Condition, QueryOrder, TransactionTrait};
+
+use super::{
+    _entities::season::{self, ActiveModel},
+    user::{LeaderboardWindow, UserWithVotes},
+};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert && self.created_at.is_not_set() {
+            self.created_at = ActiveValue::set(Utc::now().into());
+        }
+
+        Ok(self)
+    }
+}
+
+impl super::_entities::season::Model {
+    /// Past seasons are read-only archives
+    pub fn is_active(&self) -> bool {
+        let now = Utc::now();
+
+        self.starts_at <= now && self.ends_at.is_none_or(|ends_at| ends_at > now)
+    }
+
+    /// finds the season votes are currently written into
+    pub async fn find_active(db: &DatabaseConnection) -> ModelResult<Option<Self>> {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let season = season::Entity::find()
+            .filter(season::Column::StartsAt.lte(now))
+            .filter(
+                Condition::any()
+                    .add(season::Column::EndsAt.is_null())
+                    .add(season::Column::EndsAt.gt(now)),
+            )
+            .order_by_desc(season::Column::StartsAt)
+            .one(db)
+            .await?;
+
+        Ok(season)
+    }
+
+    /// finds the most recently started season, whether it is still running or
+    /// not
+    pub async fn find_latest(db: &DatabaseConnection) -> ModelResult<Option<Self>> {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let season = season::Entity::find()
+            .filter(season::Column::StartsAt.lte(now))
+            .order_by_desc(season::Column::StartsAt)
+            .one(db)
+            .await?;
+
+        Ok(season)
+    }
+
+    /// Starts a new season at `starts_at`, ending the seasons still open then
+    pub async fn start(
+        db: &DatabaseConnection,
+        name: &str,
+        starts_at: DateTime<FixedOffset>,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+
+        season::Entity::update_many()
+            .col_expr(season::Column::EndsAt, Expr::value(starts_at))
+            .filter(season::Column::StartsAt.lt(starts_at))
+            .filter(
+                Condition::any()
+                    .add(season::Column::EndsAt.is_null())
+                    .add(season::Column::EndsAt.gt(starts_at)),
+            )
+            .exec(&txn)
+            .await?;
+
+        let season = season::ActiveModel {
+            name: ActiveValue::set(name.to_string()),
+            starts_at: ActiveValue::set(starts_at),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(season)
+    }
+
+    /// Lists the ended seasons with their winner, newest first
+    pub async fn hall_of_fame(
+        db: &DatabaseConnection,
+    ) -> ModelResult<Vec<(Self, Option<UserWithVotes>)>> {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let seasons = season::Entity::find()
+            .filter(season::Column::EndsAt.lte(now))
+            .order_by_desc(season::Column::StartsAt)
+            .all(db)
+            .await?;
+
+        let mut result = Vec::with_capacity(seasons.len());
+        for season in seasons {
+            let winner = super::_entities::user::Model::find_leaderboard(
+                db,
+                &None,
+                Some(season.id),
+                LeaderboardWindow::All,
+                1,
+                1,
+            )
+            .await?
+            .into_iter()
+            .next();
+
+            result.push((season, winner));
+        }
+
+        Ok(result)
+    }
+}
@@ -1,17 +1,71 @@
-use loco_rs::{model::ModelResult, prelude::*};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use loco_rs::{
+    model::{ModelError, ModelResult},
+    prelude::*,
+};
 use sea_orm::{
-    entity::prelude::*, ActiveValue, DatabaseBackend, FromQueryResult, JoinType, QuerySelect,
-    Statement, TransactionTrait,
+    entity::prelude::*,
+    sea_query::{Alias, Expr, Func, LikeExpr, Order, Query, SelectStatement, WindowStatement},
+    ActiveValue, Condition, DatabaseBackend, FromQueryResult, JoinType, QueryOrder, QuerySelect,
+    TransactionTrait,
 };
+use serde::Deserialize;
 
-use super::_entities::{
-    user::{self, ActiveModel},
-    voter,
+use super::{
+    _entities::{
+        crush_match,
+        user::{self, ActiveModel},
+        vote_flag, voter, voter_identity,
+    },
+    voter::VoteStatus,
 };
 use crate::views::leaderboard::Pagination;
 
+#[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
+
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        if insert && self.created_at.is_not_set() {
+            self.created_at = ActiveValue::set(now);
+        }
+        self.updated_at = ActiveValue::set(now);
+
+        Ok(self)
+    }
+}
+
+/// Time window the leaderboard ranks votes over
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderboardWindow {
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[default]
+    #[serde(rename = "all")]
+    All,
+}
+
+impl LeaderboardWindow {
+    /// Votes cast before this don't count, `None` counts every vote
+    pub fn since(&self) -> Option<DateTime<FixedOffset>> {
+        let duration = match self {
+            Self::Day => Duration::hours(24),
+            Self::Week => Duration::days(7),
+            Self::Month => Duration::days(30),
+            Self::All => return None,
+        };
+
+        Some((Utc::now() - duration).into())
+    }
 }
 
 #[derive(FromQueryResult, Debug)]
@@ -19,6 +73,136 @@ pub struct UserWithVotes {
     pub votes: i64,
     pub username: String,
     pub rank: i64,
+    /// The owner hides the exact vote count
+    pub hide_count: bool,
+}
+
+/// Visibility settings the owner of a claimed profile changes, unset ones
+/// are kept
+#[derive(Deserialize, Debug, Default)]
+pub struct ProfileSettings {
+    /// Hides the user from the leaderboard
+    pub hidden: Option<bool>,
+    /// Shows the rank without the exact vote count
+    pub hide_count: Option<bool>,
+    /// Rejects new votes for the user
+    pub votes_closed: Option<bool>,
+}
+
+/// A user with every vote it received, whatever their status
+#[derive(FromQueryResult, Debug)]
+pub struct UserSummary {
+    pub id: i32,
+    pub username: String,
+    pub votes: i64,
+    pub suspended: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(FromQueryResult, Debug)]
+struct RankedCount {
+    count: i64,
+}
+
+const RANKED_TABLE: &str = "user_votes_rank";
+
+/// Every voted user with its vote count and global rank, equivalent to
+///
+/// ```sql
+/// SELECT u."username", u."hide_count", COUNT(v."id") AS "votes",
+///   ROW_NUMBER() OVER (ORDER BY COUNT(v."id") DESC, u."username") AS "rank"
+/// FROM "user" u JOIN "voter" v ON (u."id" = v."voted_user_id")
+/// WHERE v."status" = 'confirmed' AND v."season_id" = $1 AND v."created_at" >= $2
+///   AND NOT u."hidden" AND NOT u."suspended"
+///   AND NOT v."shadow"
+///   AND v."id" NOT IN (SELECT "voter_id" FROM "vote_flag" WHERE NOT "dismissed")
+/// GROUP BY u."id"
+/// ```
+///
+/// Votes from every season count when `season_id` is `None`
+fn ranked_users_query(season_id: Option<i32>, window: LeaderboardWindow) -> SelectStatement {
+    let votes = Expr::col((voter::Entity, voter::Column::Id)).count();
+
+    let mut query = Query::select()
+        .column((user::Entity, user::Column::Username))
+        .column((user::Entity, user::Column::HideCount))
+        .expr_as(votes.clone(), Alias::new("votes"))
+        .expr_window_as(
+            Func::cust(Alias::new("ROW_NUMBER")),
+            WindowStatement::new()
+                .order_by_expr(votes, Order::Desc)
+                .order_by((user::Entity, user::Column::Username), Order::Asc)
+                .to_owned(),
+            Alias::new("rank"),
+        )
+        .from(user::Entity)
+        .inner_join(
+            voter::Entity,
+            Expr::col((user::Entity, user::Column::Id))
+                .equals((voter::Entity, voter::Column::VotedUserId)),
+        )
+        .and_where(
+            Expr::col((voter::Entity, voter::Column::Status)).eq(VoteStatus::Confirmed.as_str()),
+        )
+        // users who opted out of the leaderboard or were suspended aren't
+        // ranked
+        .and_where(Expr::col((user::Entity, user::Column::Hidden)).eq(false))
+        .and_where(Expr::col((user::Entity, user::Column::Suspended)).eq(false))
+        // shadowed votes never count, their voters aren't told
+        .and_where(Expr::col((voter::Entity, voter::Column::Shadow)).eq(false))
+        // flagged votes don't count until a moderator dismisses the flags
+        .and_where(Expr::col((voter::Entity, voter::Column::Id)).not_in_subquery(flagged_votes()))
+        .group_by_col((user::Entity, user::Column::Id))
+        .group_by_col((user::Entity, user::Column::Username))
+        .group_by_col((user::Entity, user::Column::HideCount))
+        .to_owned();
+
+    if let Some(season_id) = season_id {
+        query.and_where(Expr::col((voter::Entity, voter::Column::SeasonId)).eq(season_id));
+    }
+    if let Some(since) = window.since() {
+        query.and_where(Expr::col((voter::Entity, voter::Column::CreatedAt)).gte(since));
+    }
+
+    query
+}
+
+/// Votes with a flag no moderator has dismissed yet
+fn flagged_votes() -> SelectStatement {
+    Query::select()
+        .column(vote_flag::Column::VoterId)
+        .from(vote_flag::Entity)
+        .and_where(Expr::col(vote_flag::Column::Dismissed).eq(false))
+        .to_owned()
+}
+
+/// Wraps [`ranked_users_query`] so ranks stay global while filtering by
+/// username prefix
+fn filtered_ranked_users_query(
+    username: &Option<String>,
+    season_id: Option<i32>,
+    window: LeaderboardWindow,
+) -> SelectStatement {
+    let mut query = Query::select()
+        .from_subquery(
+            ranked_users_query(season_id, window),
+            Alias::new(RANKED_TABLE),
+        )
+        .to_owned();
+
+    if let Some(username) = username.as_ref().filter(|username| !username.is_empty()) {
+        let escaped = username
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        query.and_where(
+            Expr::col((Alias::new(RANKED_TABLE), Alias::new("username")))
+                .like(LikeExpr::new(format!("{}%", escaped)).escape('\\')),
+        );
+    }
+
+    query
 }
 
 impl super::_entities::user::Model {
@@ -47,44 +231,131 @@ impl super::_entities::user::Model {
         Ok(new_user)
     }
 
+    /// finds a user by username
+    ///
+    /// # Errors
+    ///
+    /// When could not find user by the given username or DB query error
+    pub async fn find_by_username(db: &DatabaseConnection, username: &str) -> ModelResult<Self> {
+        let user = user::Entity::find()
+            .filter(user::Column::Username.eq(username))
+            .one(db)
+            .await?;
+        user.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// finds the vote count and leaderboard rank of a user in a season,
+    /// `None` when nobody voted for it
+    pub async fn find_ranked(
+        db: &DatabaseConnection,
+        username: &str,
+        season_id: Option<i32>,
+    ) -> ModelResult<Option<UserWithVotes>> {
+        let query = Query::select()
+            .columns([
+                (Alias::new(RANKED_TABLE), Alias::new("username")),
+                (Alias::new(RANKED_TABLE), Alias::new("votes")),
+                (Alias::new(RANKED_TABLE), Alias::new("rank")),
+                (Alias::new(RANKED_TABLE), Alias::new("hide_count")),
+            ])
+            .from_subquery(
+                ranked_users_query(season_id, LeaderboardWindow::All),
+                Alias::new(RANKED_TABLE),
+            )
+            .and_where(Expr::col((Alias::new(RANKED_TABLE), Alias::new("username"))).eq(username))
+            .to_owned();
+
+        let user = UserWithVotes::find_by_statement(db.get_database_backend().build(&query))
+            .one(db)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Gets when the first confirmed vote for the user was cast, in a season
+    /// or overall
+    pub async fn find_first_vote_at(
+        &self,
+        db: &DatabaseConnection,
+        season_id: Option<i32>,
+    ) -> ModelResult<Option<DateTimeWithTimeZone>> {
+        let first = self
+            .confirmed_votes(season_id)
+            .select_only()
+            .column(voter::Column::CreatedAt)
+            .order_by_asc(voter::Column::CreatedAt)
+            .into_tuple()
+            .one(db)
+            .await?;
+
+        Ok(first)
+    }
+
+    /// Counts the confirmed votes for the user per day (UTC), in a season or
+    /// overall. Days without votes are left out, oldest first.
+    pub async fn find_daily_votes(
+        &self,
+        db: &DatabaseConnection,
+        season_id: Option<i32>,
+    ) -> ModelResult<Vec<(NaiveDate, i64)>> {
+        // SQLite converts to UTC when reading the offset of the stored text
+        let day = match db.get_database_backend() {
+            DatabaseBackend::Sqlite => Expr::cust(r#"DATE("voter"."created_at")"#),
+            _ => Expr::cust(r#"CAST(("voter"."created_at" AT TIME ZONE 'UTC') AS DATE)"#),
+        };
+
+        let votes = self
+            .confirmed_votes(season_id)
+            .select_only()
+            .column_as(day.clone(), "day")
+            .column_as(voter::Column::Id.count(), "votes")
+            .group_by(day.clone())
+            .order_by_asc(day)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(votes)
+    }
+
+    fn confirmed_votes(&self, season_id: Option<i32>) -> Select<voter::Entity> {
+        let mut query = voter::Entity::find()
+            .filter(voter::Column::VotedUserId.eq(self.id))
+            .filter(voter::Column::Status.eq(VoteStatus::Confirmed.as_str()))
+            // counted like the leaderboard so the profile agrees with the rank
+            .filter(voter::Column::Shadow.eq(false))
+            .filter(voter::Column::Id.not_in_subquery(flagged_votes()));
+        if let Some(season_id) = season_id {
+            query = query.filter(voter::Column::SeasonId.eq(season_id));
+        }
+
+        query
+    }
+
     pub async fn find_leaderboard(
         db: &DatabaseConnection,
         username: &Option<String>,
+        season_id: Option<i32>,
+        window: LeaderboardWindow,
         page: u64,
         count: u64,
     ) -> ModelResult<Vec<UserWithVotes>> {
-        let username = username.as_ref().map(|s| s.as_str());
-
-        let leaderboard_query = user::Entity::find().from_raw_sql(Statement::from_sql_and_values(
-            DatabaseBackend::Postgres,
-            r#"SELECT
-            user_votes_rank."username",
-            user_votes_rank."votes",
-            user_votes_rank."rank"
-          FROM (
-            SELECT
-              u."username",
-              COUNT(v."id") AS "votes",
-              ROW_NUMBER() OVER (ORDER BY COUNT(v."voted_user_id") DESC) AS "rank"
-            FROM
-              "user" u
-              JOIN "voter" v ON (u."id" = v."voted_user_id")
-            GROUP BY
-              u."id"
-          ) AS user_votes_rank
-          WHERE
-            user_votes_rank."username" LIKE CONCAT($1, '%')
-          ORDER BY
-            user_votes_rank."rank"
-          LIMIT $2
-          OFFSET $3"#,
-            [username.into(), count.into(), ((page - 1) * count).into()],
-        ));
-
-        let users = leaderboard_query
-            .into_model::<UserWithVotes>()
-            .all(db)
-            .await?;
+        let leaderboard_query = filtered_ranked_users_query(username, season_id, window)
+            .columns([
+                (Alias::new(RANKED_TABLE), Alias::new("username")),
+                (Alias::new(RANKED_TABLE), Alias::new("votes")),
+                (Alias::new(RANKED_TABLE), Alias::new("rank")),
+                (Alias::new(RANKED_TABLE), Alias::new("hide_count")),
+            ])
+            .order_by((Alias::new(RANKED_TABLE), Alias::new("rank")), Order::Asc)
+            .limit(count)
+            .offset(page.saturating_sub(1) * count)
+            .to_owned();
+
+        let users =
+            UserWithVotes::find_by_statement(db.get_database_backend().build(&leaderboard_query))
+                .all(db)
+                .await?;
 
         Ok(users)
     }
@@ -93,13 +364,17 @@ impl super::_entities::user::Model {
         db: &DatabaseConnection,
         page_size: u64,
         username: &Option<String>,
+        season_id: Option<i32>,
+        window: LeaderboardWindow,
     ) -> ModelResult<Pagination> {
-        let username = username.clone().unwrap_or("".to_string());
+        let count_query = filtered_ranked_users_query(username, season_id, window)
+            .expr_as(Expr::cust("COUNT(*)"), Alias::new("count"))
+            .to_owned();
 
-        let entries = user::Entity::find()
-            .filter(user::Column::Username.starts_with(username))
-            .count(db)
-            .await?;
+        let entries = RankedCount::find_by_statement(db.get_database_backend().build(&count_query))
+            .one(db)
+            .await?
+            .map_or(0, |result| result.count as u64);
 
         let last = ((entries as f64) / (page_size as f64)).ceil() as u64;
 
@@ -110,6 +385,109 @@ impl super::_entities::user::Model {
         })
     }
 
+    /// Links the user to the verified identity of its threads account, the
+    /// row is created when nobody voted for it yet
+    pub async fn claim(
+        db: &DatabaseConnection,
+        username: &str,
+        identity_id: i32,
+    ) -> ModelResult<Self> {
+        let user = Self::add(db, username).await?;
+
+        if user.owner_identity_id == Some(identity_id) {
+            return Ok(user);
+        }
+
+        let user = user::ActiveModel {
+            id: ActiveValue::unchanged(user.id),
+            owner_identity_id: ActiveValue::set(Some(identity_id)),
+            claimed_at: ActiveValue::set(Some(Utc::now().into())),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// finds the user claimed by the identity
+    ///
+    /// # Errors
+    ///
+    /// When the identity didn't claim a user or DB query error
+    pub async fn find_by_owner(db: &DatabaseConnection, identity_id: i32) -> ModelResult<Self> {
+        let user = user::Entity::find()
+            .filter(user::Column::OwnerIdentityId.eq(identity_id))
+            .one(db)
+            .await?;
+        user.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    pub async fn update_settings(
+        self,
+        db: &DatabaseConnection,
+        settings: ProfileSettings,
+    ) -> ModelResult<Self> {
+        let mut user: ActiveModel = self.into();
+
+        if let Some(hidden) = settings.hidden {
+            user.hidden = ActiveValue::set(hidden);
+        }
+        if let Some(hide_count) = settings.hide_count {
+            user.hide_count = ActiveValue::set(hide_count);
+        }
+        if let Some(votes_closed) = settings.votes_closed {
+            user.votes_closed = ActiveValue::set(votes_closed);
+        }
+
+        Ok(user.update(db).await?)
+    }
+
+    /// Lists the users whose username contains `query`, alphabetically
+    pub async fn search(
+        db: &DatabaseConnection,
+        query: &Option<String>,
+        page: u64,
+        page_size: u64,
+    ) -> ModelResult<(Vec<UserSummary>, Pagination)> {
+        let mut select = user::Entity::find()
+            .select_only()
+            .columns([
+                user::Column::Id,
+                user::Column::Username,
+                user::Column::Suspended,
+                user::Column::CreatedAt,
+            ])
+            .column_as(
+                Expr::col((voter::Entity, voter::Column::Id)).count(),
+                "votes",
+            )
+            .join(JoinType::LeftJoin, user::Relation::Voter.def())
+            .group_by(user::Column::Id)
+            .group_by(user::Column::Username)
+            .group_by(user::Column::Suspended)
+            .group_by(user::Column::CreatedAt)
+            .order_by_asc(user::Column::Username);
+
+        if let Some(query) = query.as_ref().filter(|query| !query.is_empty()) {
+            select = select.filter(user::Column::Username.contains(query.to_lowercase()));
+        }
+
+        let paginator = select.into_model::<UserSummary>().paginate(db, page_size);
+        let counts = paginator.num_items_and_pages().await?;
+        let users = paginator.fetch_page(page.saturating_sub(1)).await?;
+
+        Ok((
+            users,
+            Pagination {
+                current: page,
+                last: counts.number_of_pages,
+                entries: counts.number_of_items,
+            },
+        ))
+    }
+
+    /// finds the user an address hash voted for
     pub async fn find_voted_user_by_address(
         db: &DatabaseConnection,
         address: &String,
@@ -122,4 +500,120 @@ impl super::_entities::user::Model {
 
         Ok(user)
     }
+
+    /// Deletes a user together with the votes it received, returns how many
+    /// votes were deleted
+    ///
+    /// # Errors
+    ///
+    /// When could not find user by the given username or DB query error
+    pub async fn delete_with_votes(db: &DatabaseConnection, username: &str) -> ModelResult<u64> {
+        let txn = db.begin().await?;
+
+        let user = user::Entity::find()
+            .filter(user::Column::Username.eq(username))
+            .one(&txn)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)?;
+
+        let mut identity_ids: Vec<i32> = voter::Entity::find()
+            .select_only()
+            .column(voter::Column::IdentityId)
+            .filter(voter::Column::VotedUserId.eq(user.id))
+            .filter(voter::Column::IdentityId.is_not_null())
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        // the matches of the banned account itself go too
+        identity_ids.extend(user.owner_identity_id);
+        identity_ids.extend(
+            voter_identity::Entity::find()
+                .select_only()
+                .column(voter_identity::Column::Id)
+                .filter(voter_identity::Column::Username.eq(username))
+                .into_tuple::<i32>()
+                .one(&txn)
+                .await?,
+        );
+        crush_match::Model::delete_for_identities(&txn, &identity_ids, None).await?;
+
+        let votes = voter::Entity::delete_many()
+            .filter(voter::Column::VotedUserId.eq(user.id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+
+        user.delete(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(votes)
+    }
+
+    /// Suspended users are left out of the leaderboard and can't get new
+    /// votes
+    ///
+    /// # Errors
+    ///
+    /// When could not find user by the given username or DB query error
+    pub async fn set_suspended(
+        db: &DatabaseConnection,
+        username: &str,
+        suspended: bool,
+    ) -> ModelResult<Self> {
+        let user = Self::find_by_username(db, username).await?;
+
+        let user = user::ActiveModel {
+            id: ActiveValue::unchanged(user.id),
+            suspended: ActiveValue::set(suspended),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Deletes the users nobody voted for, returns how many were deleted.
+    /// Claimed and suspended users are kept so their settings stick.
+    pub async fn prune_orphans(db: &DatabaseConnection) -> ModelResult<u64> {
+        let deleted = user::Entity::delete_many()
+            .filter(orphans())
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(deleted)
+    }
+
+    /// Deletes the user if nobody votes for it anymore, like
+    /// [`Self::prune_orphans`]. Returns whether it was deleted.
+    pub async fn delete_if_orphan<C>(db: &C, id: i32) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let deleted = user::Entity::delete_many()
+            .filter(user::Column::Id.eq(id))
+            .filter(orphans())
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(deleted > 0)
+    }
+}
+
+/// Users nobody voted for that aren't claimed or suspended
+fn orphans() -> Condition {
+    Condition::all()
+        .add(user::Column::OwnerIdentityId.is_null())
+        .add(user::Column::Suspended.eq(false))
+        .add(
+            user::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(voter::Column::VotedUserId)
+                    .from(voter::Entity)
+                    .to_owned(),
+            ),
+        )
 }
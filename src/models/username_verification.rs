@@ -0,0 +1,88 @@
+use chrono::{Duration, Utc};
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, ActiveValue};
+
+use super::_entities::username_verification::{self, ActiveModel};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Outcome of a username lookup, stored in `username_verification.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationStatus {
+    Exists,
+    NotFound,
+    Error,
+}
+
+impl VerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exists => "exists",
+            Self::NotFound => "not_found",
+            Self::Error => "error",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "exists" => Some(Self::Exists),
+            "not_found" => Some(Self::NotFound),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+impl super::_entities::username_verification::Model {
+    pub fn status(&self) -> Option<VerificationStatus> {
+        VerificationStatus::parse(&self.status)
+    }
+
+    /// Checks if the result is younger than `ttl`
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        self.verified_at.with_timezone(&Utc) + ttl > Utc::now()
+    }
+
+    /// finds the last lookup result for a username
+    pub async fn find_by_username(
+        db: &DatabaseConnection,
+        username: &str,
+    ) -> ModelResult<Option<Self>> {
+        let verification = username_verification::Entity::find()
+            .filter(username_verification::Column::Username.eq(username))
+            .one(db)
+            .await?;
+
+        Ok(verification)
+    }
+
+    /// Stores the result of a lookup, replacing the previous one
+    pub async fn record(
+        db: &DatabaseConnection,
+        username: &str,
+        status: VerificationStatus,
+    ) -> ModelResult<()> {
+        let verification = username_verification::ActiveModel {
+            username: ActiveValue::set(username.to_string()),
+            status: ActiveValue::set(status.as_str().to_string()),
+            verified_at: ActiveValue::set(Utc::now().into()),
+            ..Default::default()
+        };
+
+        username_verification::Entity::insert(verification)
+            .on_conflict(
+                OnConflict::column(username_verification::Column::Username)
+                    .update_columns([
+                        username_verification::Column::Status,
+                        username_verification::Column::VerifiedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
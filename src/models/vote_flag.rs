@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use loco_rs::model::ModelResult;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{OnConflict, Query},
+    ActiveValue, JoinType, QuerySelect,
+};
+use serde::Serialize;
+
+use super::_entities::{
+    user,
+    vote_flag::{self, ActiveModel},
+    voter,
+};
+use crate::anomaly::Flag;
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Why the analysis flagged a vote, stored in `vote_flag.kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagKind {
+    /// One of many votes for the user from the same /24 or /48
+    NetworkCluster,
+    /// Cast in an hour with far more votes than the user usually gets
+    Spike,
+    /// Part of a burst of votes cast at suspiciously regular intervals
+    EvenTiming,
+    /// Cast from an address on an IP list with the `flag` policy
+    IpList,
+    /// Scored as high risk when cast, before those votes were shadowed
+    /// instead. Only found in older rows.
+    HighRisk,
+}
+
+impl FlagKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NetworkCluster => "network_cluster",
+            Self::Spike => "spike",
+            Self::EvenTiming => "even_timing",
+            Self::IpList => "ip_list",
+            Self::HighRisk => "high_risk",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "network_cluster" => Some(Self::NetworkCluster),
+            "spike" => Some(Self::Spike),
+            "even_timing" => Some(Self::EvenTiming),
+            "ip_list" => Some(Self::IpList),
+            "high_risk" => Some(Self::HighRisk),
+            _ => None,
+        }
+    }
+}
+
+/// A user with votes flagged by the analysis and not dismissed yet
+#[derive(Debug)]
+pub struct FlaggedUser {
+    pub username: String,
+    pub flagged_votes: u64,
+    pub kinds: Vec<FlagKind>,
+}
+
+impl super::_entities::vote_flag::Model {
+    pub fn kind(&self) -> Option<FlagKind> {
+        FlagKind::parse(&self.kind)
+    }
+
+    /// Stores the flags, votes already flagged for the same reason are
+    /// skipped. Returns how many flags were new.
+    pub async fn record<C>(db: &C, flags: &[Flag]) -> ModelResult<u64>
+    where
+        C: ConnectionTrait,
+    {
+        let mut recorded = 0;
+
+        for flags in flags.chunks(500) {
+            recorded +=
+                vote_flag::Entity::insert_many(flags.iter().map(|flag| vote_flag::ActiveModel {
+                    voter_id: ActiveValue::set(flag.voter_id),
+                    kind: ActiveValue::set(flag.kind.as_str().to_string()),
+                    detail: ActiveValue::set(flag.detail.clone()),
+                    ..Default::default()
+                }))
+                .on_conflict(
+                    OnConflict::columns([vote_flag::Column::VoterId, vote_flag::Column::Kind])
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(db)
+                .await?;
+        }
+
+        Ok(recorded)
+    }
+
+    /// Lists the users with flagged votes, most flagged first
+    pub async fn flagged_users(db: &DatabaseConnection) -> ModelResult<Vec<FlaggedUser>> {
+        let flags: Vec<(i32, String, String)> = vote_flag::Entity::find()
+            .select_only()
+            .column(vote_flag::Column::VoterId)
+            .column(vote_flag::Column::Kind)
+            .column(user::Column::Username)
+            .join(JoinType::InnerJoin, vote_flag::Relation::Voter.def())
+            .join(JoinType::InnerJoin, voter::Relation::User.def())
+            .filter(vote_flag::Column::Dismissed.eq(false))
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut users: BTreeMap<String, (BTreeSet<i32>, BTreeSet<FlagKind>)> = BTreeMap::new();
+        for (voter_id, kind, username) in flags {
+            let (votes, kinds) = users.entry(username).or_default();
+            votes.insert(voter_id);
+            kinds.extend(FlagKind::parse(&kind));
+        }
+
+        let mut users: Vec<FlaggedUser> = users
+            .into_iter()
+            .map(|(username, (votes, kinds))| FlaggedUser {
+                username,
+                flagged_votes: votes.len() as u64,
+                kinds: kinds.into_iter().collect(),
+            })
+            .collect();
+        users.sort_by_key(|user| std::cmp::Reverse(user.flagged_votes));
+
+        Ok(users)
+    }
+
+    /// Dismisses the flags on the votes for a user, so they count again.
+    /// Returns how many flags were dismissed.
+    pub async fn dismiss_for_user(db: &DatabaseConnection, user_id: i32) -> ModelResult<u64> {
+        let dismissed = vote_flag::Entity::update_many()
+            .col_expr(vote_flag::Column::Dismissed, Expr::value(true))
+            .filter(vote_flag::Column::Dismissed.eq(false))
+            .filter(
+                vote_flag::Column::VoterId.in_subquery(
+                    Query::select()
+                        .column(voter::Column::Id)
+                        .from(voter::Entity)
+                        .and_where(voter::Column::VotedUserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(dismissed)
+    }
+}
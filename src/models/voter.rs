@@ -1,10 +1,142 @@
+use chrono::{DateTime, FixedOffset, Utc};
 use loco_rs::model::{ModelError, ModelResult};
-use sea_orm::{entity::prelude::*, ActiveValue, TransactionTrait};
+use sea_orm::{
+    entity::prelude::*, ActiveValue, Condition, DbBackend, QueryOrder, QuerySelect, Statement,
+    TransactionTrait,
+};
+use serde::Serialize;
 
-use super::_entities::voter::{self, ActiveModel};
+use super::{
+    _entities::{
+        crush_match, user, vote_flag,
+        voter::{self, ActiveModel},
+    },
+    vote_flag::FlagKind,
+};
+use crate::{
+    anomaly::{Flag, VoteSample},
+    utils::address_hash::VoterAddress,
+};
 
+#[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
+
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        if insert && self.created_at.is_not_set() {
+            self.created_at = ActiveValue::set(now);
+        }
+        self.updated_at = ActiveValue::set(now);
+
+        Ok(self)
+    }
+}
+
+/// Verification state of a vote, stored in `voter.status`. Only confirmed
+/// votes count towards the leaderboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteStatus {
+    /// The username has not been checked yet
+    Pending,
+    Confirmed,
+    /// The username does not exist on threads
+    NotFound,
+    /// The username could not be checked
+    Failed,
+    /// Thrown out by a moderator, the voter can't vote again in the season
+    Invalidated,
+    /// Unvoted while shadowed, kept so a new vote is shadowed too
+    Withdrawn,
+}
+
+impl VoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::NotFound => "not_found",
+            Self::Failed => "failed",
+            Self::Invalidated => "invalidated",
+            Self::Withdrawn => "withdrawn",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(Self::Pending),
+            "confirmed" => Some(Self::Confirmed),
+            "not_found" => Some(Self::NotFound),
+            "failed" => Some(Self::Failed),
+            "invalidated" => Some(Self::Invalidated),
+            "withdrawn" => Some(Self::Withdrawn),
+            _ => None,
+        }
+    }
+
+    /// Rejected and withdrawn votes don't count and can be replaced by a new
+    /// vote
+    pub fn is_replaceable(&self) -> bool {
+        matches!(self, Self::NotFound | Self::Failed | Self::Withdrawn)
+    }
+
+    /// Stored values of the replaceable statuses
+    fn replaceable() -> [&'static str; 3] {
+        [
+            Self::NotFound.as_str(),
+            Self::Failed.as_str(),
+            Self::Withdrawn.as_str(),
+        ]
+    }
+}
+
+/// What votes are deduplicated on within a season
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoterKey<'a> {
+    /// Anonymous voters, one vote per hash of what the voter identity
+    /// resolver picked (address, network or cookie)
+    Address(&'a str),
+    /// Verified voters, one vote per identity wherever they vote from. Also
+    /// takes the anonymous key of where they vote from, when there's one, so
+    /// they can't vote both ways from there.
+    Identity(i32, Option<&'a str>),
+}
+
+impl VoterKey<'_> {
+    fn condition(&self, season_id: i32) -> Condition {
+        let condition = Condition::all().add(voter::Column::SeasonId.eq(season_id));
+
+        match self {
+            Self::Address(address) => condition
+                .add(voter::Column::Address.eq(*address))
+                .add(voter::Column::IdentityId.is_null()),
+            Self::Identity(identity_id, _) => {
+                condition.add(voter::Column::IdentityId.eq(*identity_id))
+            }
+        }
+    }
+
+    fn identity_id(&self) -> Option<i32> {
+        match self {
+            Self::Address(_) => None,
+            Self::Identity(identity_id, _) => Some(*identity_id),
+        }
+    }
+}
+
+/// What the screening of a vote stores with it, written with the vote so it
+/// never counts unscreened
+#[derive(Debug, Default)]
+pub struct Screened {
+    /// The vote is left out of the leaderboard, its voter isn't told
+    pub shadow: bool,
+    /// Kept out of the leaderboard until a moderator dismisses it
+    pub flag: Option<(FlagKind, String)>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -12,6 +144,9 @@ pub enum VoterError {
     #[error("Already voted")]
     AlreadyVoted,
 
+    #[error("Too many votes from this address")]
+    AddressCapReached,
+
     #[error(transparent)]
     ModelError(#[from] ModelError),
 }
@@ -21,67 +156,580 @@ pub enum DeleteVoterError {
     #[error("Voter not found")]
     NotFound,
 
+    #[error("Vote was invalidated")]
+    Invalidated,
+
+    #[error(transparent)]
+    ModelError(#[from] ModelError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ChangeVoteError {
+    #[error("Voter not found")]
+    NotFound,
+
+    #[error("Vote was invalidated")]
+    Invalidated,
+
     #[error(transparent)]
     ModelError(#[from] ModelError),
 }
 
 impl super::_entities::voter::Model {
-    /// finds a voter by ip address
+    pub fn status(&self) -> VoteStatus {
+        VoteStatus::parse(&self.status).unwrap_or(VoteStatus::Pending)
+    }
+
+    /// finds a voter and the user it voted for, withdrawn votes are left out
+    pub async fn find_with_user(
+        db: &DatabaseConnection,
+        key: VoterKey<'_>,
+        season_id: i32,
+    ) -> ModelResult<Option<(Self, Option<user::Model>)>> {
+        let voter = voter::Entity::find()
+            .filter(key.condition(season_id))
+            .filter(voter::Column::Status.ne(VoteStatus::Withdrawn.as_str()))
+            .find_also_related(user::Entity)
+            .one(db)
+            .await?;
+
+        Ok(voter)
+    }
+
+    /// finds an anonymous voter by address hash
     ///
     /// # Errors
     ///
     /// When could not find user by the given address or DB query error
-    pub async fn find_by_address(db: &DatabaseConnection, address: &str) -> ModelResult<Self> {
+    pub async fn find_by_address(
+        db: &DatabaseConnection,
+        address: &str,
+        season_id: i32,
+    ) -> ModelResult<Self> {
         let voter = voter::Entity::find()
-            .filter(voter::Column::Address.eq(address))
+            .filter(VoterKey::Address(address).condition(season_id))
             .one(db)
             .await?;
         voter.ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    /// Adds a new voter to the db
+    /// Adds a new voter to the season, replacing a previously rejected vote
+    /// with the same key. Anonymous voters get at most `cap` votes per
+    /// address.
+    #[allow(clippy::too_many_arguments)]
     pub async fn add(
         db: &DatabaseConnection,
-        address: &str,
+        key: VoterKey<'_>,
+        season_id: i32,
+        address: &VoterAddress,
+        cap: Option<u32>,
         voted_user_id: i32,
+        status: VoteStatus,
+        screened: Screened,
     ) -> Result<Self, VoterError> {
         let txn = db.begin().await.map_err(ModelError::from)?;
 
-        if voter::Entity::find()
-            .filter(voter::Column::Address.eq(address))
+        // verified voters are deduplicated on their identity, their votes keep
+        // the anonymous key they were cast from
+        let key_hash = match key {
+            VoterKey::Address(hash) | VoterKey::Identity(_, Some(hash)) => hash,
+            VoterKey::Identity(_, None) => address.hash.as_str(),
+        };
+
+        // new votes of shadowed voters are shadowed too, so they can't tell.
+        // Looked up before the existing vote is replaced, it may be a
+        // withdrawn shadowed one.
+        let mut shadowed = Condition::any()
+            .add(voter::Column::Address.eq(key_hash))
+            .add(voter::Column::IpHash.eq(address.hash.as_str()));
+        if let Some(identity_id) = key.identity_id() {
+            shadowed = shadowed.add(voter::Column::IdentityId.eq(identity_id));
+        }
+        let shadow = screened.shadow
+            || voter::Entity::find()
+                .filter(voter::Column::Shadow.eq(true))
+                .filter(shadowed)
+                .count(&txn)
+                .await
+                .map_err(ModelError::from)?
+                > 0;
+
+        if let Some(existing) = voter::Entity::find()
+            .filter(key.condition(season_id))
             .one(&txn)
             .await
             .map_err(ModelError::from)?
-            .is_some()
         {
+            if !existing.status().is_replaceable() {
+                return Err(VoterError::AlreadyVoted);
+            }
+
+            existing.delete(&txn).await.map_err(ModelError::from)?;
+        }
+
+        // a voter can't vote anonymously and as a verified voter from the
+        // same place
+        let other_kind = match key {
+            VoterKey::Address(_) => voter::Column::IdentityId.is_not_null(),
+            VoterKey::Identity(..) => voter::Column::IdentityId.is_null(),
+        };
+        let voted_other_kind = voter::Entity::find()
+            .filter(voter::Column::SeasonId.eq(season_id))
+            .filter(voter::Column::Address.eq(key_hash))
+            .filter(other_kind)
+            .filter(voter::Column::Status.is_not_in(VoteStatus::replaceable()))
+            .count(&txn)
+            .await
+            .map_err(ModelError::from)?
+            > 0;
+        if voted_other_kind {
             return Err(VoterError::AlreadyVoted);
         }
 
+        if let (VoterKey::Address(_), Some(cap)) = (key, cap) {
+            // votes from the address are counted and added one at a time, or
+            // concurrent votes could all get under the cap
+            if txn.get_database_backend() == DbBackend::Postgres {
+                txn.execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    "SELECT pg_advisory_xact_lock(hashtext($1))",
+                    [address.hash.as_str().into()],
+                ))
+                .await
+                .map_err(ModelError::from)?;
+            }
+
+            let votes = voter::Entity::find()
+                .filter(voter::Column::SeasonId.eq(season_id))
+                .filter(voter::Column::IpHash.eq(address.hash.as_str()))
+                .filter(voter::Column::IdentityId.is_null())
+                .filter(voter::Column::Status.is_not_in(VoteStatus::replaceable()))
+                .count(&txn)
+                .await
+                .map_err(ModelError::from)?;
+
+            if votes >= u64::from(cap) {
+                return Err(VoterError::AddressCapReached);
+            }
+        }
+
         let voter = voter::ActiveModel {
-            address: ActiveValue::set(address.to_string()),
+            address: ActiveValue::set(key_hash.to_string()),
+            ip_hash: ActiveValue::set(Some(address.hash.clone())),
+            network: ActiveValue::set(address.network.clone()),
             voted_user_id: ActiveValue::set(voted_user_id),
+            status: ActiveValue::set(status.as_str().to_string()),
+            identity_id: ActiveValue::set(key.identity_id()),
+            season_id: ActiveValue::set(Some(season_id)),
+            shadow: ActiveValue::set(shadow),
             ..Default::default()
         }
         .insert(&txn)
         .await
         .map_err(ModelError::from)?;
+        record_flag(&txn, voter.id, screened.flag).await?;
+
+        txn.commit().await.map_err(ModelError::from)?;
+
+        Ok(voter)
+    }
+
+    /// Moves a vote to another user in one transaction, the new username must
+    /// already be verified. Returns the user the vote was for before and the
+    /// updated voter.
+    pub async fn change(
+        db: &DatabaseConnection,
+        key: VoterKey<'_>,
+        season_id: i32,
+        voted_user_id: i32,
+        screened: Screened,
+    ) -> Result<(Option<user::Model>, Self), ChangeVoteError> {
+        let txn = db.begin().await.map_err(ModelError::from)?;
+
+        let (voter, previous) = voter::Entity::find()
+            .filter(key.condition(season_id))
+            .filter(voter::Column::Status.ne(VoteStatus::Withdrawn.as_str()))
+            .find_also_related(user::Entity)
+            .one(&txn)
+            .await
+            .map_err(ModelError::from)?
+            .ok_or(ChangeVoteError::NotFound)?;
+
+        if voter.status() == VoteStatus::Invalidated {
+            return Err(ChangeVoteError::Invalidated);
+        }
+
+        if voter.voted_user_id == voted_user_id && voter.status() == VoteStatus::Confirmed {
+            txn.commit().await.map_err(ModelError::from)?;
+            return Ok((previous, voter));
+        }
+
+        if let Some(identity_id) = voter.identity_id {
+            crush_match::Model::delete_for_identities(&txn, &[identity_id], voter.season_id)
+                .await?;
+        }
+
+        let voter = voter::ActiveModel {
+            id: ActiveValue::unchanged(voter.id),
+            voted_user_id: ActiveValue::set(voted_user_id),
+            status: ActiveValue::set(VoteStatus::Confirmed.as_str().to_string()),
+            // a shadowed vote stays shadowed wherever it's moved
+            shadow: ActiveValue::set(voter.shadow || screened.shadow),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await
+        .map_err(ModelError::from)?;
+        record_flag(&txn, voter.id, screened.flag).await?;
 
         txn.commit().await.map_err(ModelError::from)?;
 
+        Ok((previous, voter))
+    }
+
+    /// Re-hashes the votes stored with the secret being rotated out, returns
+    /// how many were updated. Only writes when such a vote is found, as it
+    /// runs on every request while a secret is rotated.
+    pub async fn rekey(
+        db: &DatabaseConnection,
+        key: &VoterAddress,
+        address: &VoterAddress,
+    ) -> ModelResult<u64> {
+        let mut updated = 0;
+
+        for (column, hash, previous_hash) in [
+            (
+                voter::Column::Address,
+                Some(&key.hash),
+                key.previous_hash.as_ref(),
+            ),
+            (
+                voter::Column::IpHash,
+                Some(&address.hash),
+                address.previous_hash.as_ref(),
+            ),
+            (
+                voter::Column::Network,
+                address.network.as_ref(),
+                address.previous_network.as_ref(),
+            ),
+        ] {
+            let (Some(hash), Some(previous_hash)) = (hash, previous_hash) else {
+                continue;
+            };
+
+            let legacy = voter::Entity::find()
+                .select_only()
+                .column(voter::Column::Id)
+                .filter(column.eq(previous_hash.as_str()))
+                .into_tuple::<i32>()
+                .one(db)
+                .await?;
+            if legacy.is_none() {
+                continue;
+            }
+
+            updated += voter::Entity::update_many()
+                .col_expr(column, Expr::value(hash.as_str()))
+                .filter(column.eq(previous_hash.as_str()))
+                .exec(db)
+                .await?
+                .rows_affected;
+        }
+
+        Ok(updated)
+    }
+
+    /// Records the outcome of the username verification
+    pub async fn set_status(
+        db: &DatabaseConnection,
+        id: i32,
+        status: VoteStatus,
+    ) -> ModelResult<Self> {
+        let voter = voter::ActiveModel {
+            id: ActiveValue::unchanged(id),
+            status: ActiveValue::set(status.as_str().to_string()),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+
         Ok(voter)
     }
 
-    /// Deletes a voter from the db
-    pub async fn delete(db: &DatabaseConnection, address: &str) -> Result<(), DeleteVoterError> {
+    /// Deletes a voter from the season. Invalidated votes are kept, so their
+    /// voters can't vote again, and shadowed votes are marked withdrawn, so
+    /// their voters stay shadowed.
+    pub async fn delete(
+        db: &DatabaseConnection,
+        key: VoterKey<'_>,
+        season_id: i32,
+    ) -> Result<(), DeleteVoterError> {
         let voter = voter::Entity::find()
-            .filter(voter::Column::Address.eq(address))
+            .filter(key.condition(season_id))
+            .filter(voter::Column::Status.ne(VoteStatus::Withdrawn.as_str()))
             .one(db)
             .await
             .map_err(ModelError::from)?
             .ok_or(DeleteVoterError::NotFound)?;
+        if voter.status() == VoteStatus::Invalidated {
+            return Err(DeleteVoterError::Invalidated);
+        }
+        let identity_id = voter.identity_id;
 
-        voter.delete(db).await.map_err(ModelError::from)?;
+        if voter.shadow {
+            Self::set_status(db, voter.id, VoteStatus::Withdrawn).await?;
+        } else {
+            voter.delete(db).await.map_err(ModelError::from)?;
+        }
+
+        if let Some(identity_id) = identity_id {
+            crush_match::Model::delete_for_identities(db, &[identity_id], Some(season_id)).await?;
+        }
 
         Ok(())
     }
+
+    /// Deletes the voters created before `before` and/or cast from the
+    /// stored /24 or /48 `networks`, returns how many were deleted
+    pub async fn purge(
+        db: &DatabaseConnection,
+        before: Option<DateTime<FixedOffset>>,
+        networks: Option<&[String]>,
+    ) -> ModelResult<u64> {
+        let mut query = voter::Entity::delete_many();
+
+        if let Some(before) = before {
+            query = query.filter(voter::Column::CreatedAt.lt(before));
+        }
+        if let Some(networks) = networks {
+            query = query.filter(voter::Column::Network.is_in(networks.iter().map(String::as_str)));
+        }
+
+        Ok(query.exec(db).await?.rows_affected)
+    }
+
+    /// finds the latest votes cast from an address, by the hashes of the
+    /// address with the current and previous secret
+    pub async fn find_by_ip_hashes(
+        db: &DatabaseConnection,
+        hashes: &[&str],
+        limit: u64,
+    ) -> ModelResult<Vec<(Self, Option<user::Model>)>> {
+        let voters = voter::Entity::find()
+            .filter(voter::Column::IpHash.is_in(hashes.iter().copied()))
+            .find_also_related(user::Entity)
+            .order_by_desc(voter::Column::CreatedAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(voters)
+    }
+
+    /// finds the latest votes cast from the stored /24 or /48 `networks`
+    pub async fn find_in_network(
+        db: &DatabaseConnection,
+        networks: &[String],
+        limit: u64,
+    ) -> ModelResult<Vec<(Self, Option<user::Model>)>> {
+        let voters = voter::Entity::find()
+            .filter(voter::Column::Network.is_in(networks.iter().map(String::as_str)))
+            .find_also_related(user::Entity)
+            .order_by_desc(voter::Column::CreatedAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(voters)
+    }
+
+    /// Invalidates the votes cast between `from` and `to`, optionally only
+    /// the ones cast from the stored /24 or /48 `networks`. Returns how many
+    /// were invalidated.
+    pub async fn invalidate(
+        db: &DatabaseConnection,
+        from: DateTime<FixedOffset>,
+        to: DateTime<FixedOffset>,
+        networks: Option<&[String]>,
+    ) -> ModelResult<u64> {
+        let mut condition = Condition::all()
+            .add(voter::Column::CreatedAt.gte(from))
+            .add(voter::Column::CreatedAt.lt(to))
+            .add(voter::Column::Status.ne(VoteStatus::Invalidated.as_str()));
+        if let Some(networks) = networks {
+            condition =
+                condition.add(voter::Column::Network.is_in(networks.iter().map(String::as_str)));
+        }
+
+        let txn = db.begin().await?;
+
+        let identities: Vec<(i32, Option<i32>)> = voter::Entity::find()
+            .select_only()
+            .columns([voter::Column::IdentityId, voter::Column::SeasonId])
+            .filter(condition.clone())
+            .filter(voter::Column::IdentityId.is_not_null())
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        for (identity_id, season_id) in identities {
+            crush_match::Model::delete_for_identities(&txn, &[identity_id], season_id).await?;
+        }
+
+        let invalidated = voter::Entity::update_many()
+            .col_expr(
+                voter::Column::Status,
+                Expr::value(VoteStatus::Invalidated.as_str()),
+            )
+            .filter(condition)
+            .exec(&txn)
+            .await?
+            .rows_affected;
+
+        txn.commit().await?;
+
+        Ok(invalidated)
+    }
+
+    /// Shadows (or unshadows) the votes, they are still answered as usual but
+    /// left out of the leaderboard. Returns how many were changed.
+    pub async fn set_shadow(
+        db: &DatabaseConnection,
+        ids: &[i32],
+        shadow: bool,
+    ) -> ModelResult<u64> {
+        let mut updated = 0;
+
+        for ids in ids.chunks(500) {
+            updated += voter::Entity::update_many()
+                .col_expr(voter::Column::Shadow, Expr::value(shadow))
+                .filter(voter::Column::Id.is_in(ids.iter().copied()))
+                .filter(voter::Column::Shadow.ne(shadow))
+                .exec(db)
+                .await?
+                .rows_affected;
+        }
+
+        Ok(updated)
+    }
+
+    /// Shadows (or unshadows) every vote cast from an address, by the hashes
+    /// of the address with the current and previous secret
+    pub async fn set_shadow_by_ip_hashes(
+        db: &DatabaseConnection,
+        hashes: &[&str],
+        shadow: bool,
+    ) -> ModelResult<u64> {
+        let updated = voter::Entity::update_many()
+            .col_expr(voter::Column::Shadow, Expr::value(shadow))
+            .filter(voter::Column::IpHash.is_in(hashes.iter().copied()))
+            .filter(voter::Column::Shadow.ne(shadow))
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(updated)
+    }
+
+    /// Shadows (or unshadows) every vote cast from the stored /24 or /48
+    /// `networks`
+    pub async fn set_shadow_in_network(
+        db: &DatabaseConnection,
+        networks: &[String],
+        shadow: bool,
+    ) -> ModelResult<u64> {
+        let updated = voter::Entity::update_many()
+            .col_expr(voter::Column::Shadow, Expr::value(shadow))
+            .filter(voter::Column::Network.is_in(networks.iter().map(String::as_str)))
+            .filter(voter::Column::Shadow.ne(shadow))
+            .exec(db)
+            .await?
+            .rows_affected;
+
+        Ok(updated)
+    }
+
+    /// Counts the votes cast since `since` from the address and from its /24
+    /// or /48, whatever their season or status. Returns both counts, in that
+    /// order.
+    pub async fn count_recent(
+        db: &DatabaseConnection,
+        address: &VoterAddress,
+        since: DateTime<FixedOffset>,
+    ) -> ModelResult<(u64, u64)> {
+        let from_address = voter::Entity::find()
+            .filter(voter::Column::IpHash.eq(address.hash.as_str()))
+            .filter(voter::Column::CreatedAt.gte(since))
+            .count(db)
+            .await?;
+
+        let from_network = match &address.network {
+            Some(network) => {
+                voter::Entity::find()
+                    .filter(voter::Column::Network.eq(network.as_str()))
+                    .filter(voter::Column::CreatedAt.gte(since))
+                    .count(db)
+                    .await?
+            }
+            None => from_address,
+        };
+
+        Ok((from_address, from_network))
+    }
+
+    /// Gets what the anomaly analysis looks at in the votes of a season that
+    /// weren't rejected or invalidated
+    pub async fn find_samples(
+        db: &DatabaseConnection,
+        season_id: i32,
+    ) -> ModelResult<Vec<VoteSample>> {
+        let votes: Vec<(i32, i32, Option<String>, DateTimeWithTimeZone)> = voter::Entity::find()
+            .select_only()
+            .columns([
+                voter::Column::Id,
+                voter::Column::VotedUserId,
+                voter::Column::Network,
+                voter::Column::CreatedAt,
+            ])
+            .filter(voter::Column::SeasonId.eq(season_id))
+            .filter(
+                voter::Column::Status
+                    .is_in([VoteStatus::Pending.as_str(), VoteStatus::Confirmed.as_str()]),
+            )
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(votes
+            .into_iter()
+            .map(
+                |(voter_id, voted_user_id, network, created_at)| VoteSample {
+                    voter_id,
+                    voted_user_id,
+                    network,
+                    created_at: created_at.with_timezone(&Utc),
+                },
+            )
+            .collect())
+    }
+}
+
+/// Stores the flag the screening gave the vote, in the transaction writing it
+async fn record_flag<C>(db: &C, voter_id: i32, flag: Option<(FlagKind, String)>) -> ModelResult<()>
+where
+    C: ConnectionTrait,
+{
+    if let Some((kind, detail)) = flag {
+        vote_flag::Model::record(
+            db,
+            &[Flag {
+                voter_id,
+                kind,
+                detail,
+            }],
+        )
+        .await?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,137 @@
+use chrono::{Duration, Utc};
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, TransactionTrait, TryIntoModel};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::_entities::voter_identity::{self, ActiveModel};
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// How long a challenge stays open, a new claim for the username is refused
+/// until then
+const CLAIM_TTL_MINUTES: i64 = 30;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClaimError {
+    #[error("A challenge is already in progress for this username")]
+    Pending,
+
+    #[error(transparent)]
+    ModelError(#[from] ModelError),
+}
+
+/// Only hashes of the tokens handed to voters are stored
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// A random code short enough to paste in a threads bio
+fn new_code() -> String {
+    format!("crush-{}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
+impl super::_entities::voter_identity::Model {
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    /// Starts a challenge for a threads username, returns the identity and the
+    /// token that authenticates the voter once the challenge is passed.
+    ///
+    /// A verified identity keeps its current token until the new challenge is
+    /// passed, so claims by someone else don't lock the owner out. A challenge
+    /// can't be replaced before it expires, so it can't be swapped for
+    /// another code while the owner is pasting it.
+    pub async fn claim(
+        db: &DatabaseConnection,
+        username: &str,
+    ) -> Result<(Self, String), ClaimError> {
+        let token = Uuid::new_v4().simple().to_string();
+        let now = Utc::now();
+
+        let txn = db.begin().await.map_err(ModelError::from)?;
+
+        let existing = voter_identity::Entity::find()
+            .filter(voter_identity::Column::Username.eq(username))
+            .one(&txn)
+            .await
+            .map_err(ModelError::from)?;
+
+        let mut identity = match existing {
+            Some(existing) => {
+                if existing.pending_token_hash.is_some()
+                    && existing.claimed_at.is_some_and(|claimed_at| {
+                        claimed_at > now - Duration::minutes(CLAIM_TTL_MINUTES)
+                    })
+                {
+                    return Err(ClaimError::Pending);
+                }
+
+                existing.into_active_model()
+            }
+            None => voter_identity::ActiveModel {
+                username: ActiveValue::set(username.to_string()),
+                ..Default::default()
+            },
+        };
+        identity.code = ActiveValue::set(Some(new_code()));
+        identity.pending_token_hash = ActiveValue::set(Some(hash_token(&token)));
+        identity.claimed_at = ActiveValue::set(Some(now.into()));
+
+        let identity = identity
+            .save(&txn)
+            .await
+            .map_err(ModelError::from)?
+            .try_into_model()
+            .map_err(ModelError::from)?;
+
+        txn.commit().await.map_err(ModelError::from)?;
+
+        Ok((identity, token))
+    }
+
+    /// finds the identity with an unexpired challenge in progress for the
+    /// token
+    pub async fn find_by_pending_token(db: &DatabaseConnection, token: &str) -> ModelResult<Self> {
+        let expired_at = Utc::now() - Duration::minutes(CLAIM_TTL_MINUTES);
+
+        let identity = voter_identity::Entity::find()
+            .filter(voter_identity::Column::PendingTokenHash.eq(hash_token(token)))
+            .filter(voter_identity::Column::ClaimedAt.gt(expired_at))
+            .one(db)
+            .await?;
+        identity.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// finds the verified identity authenticated by the token
+    pub async fn find_verified_by_token(
+        db: &DatabaseConnection,
+        token: &str,
+    ) -> ModelResult<Option<Self>> {
+        let identity = voter_identity::Entity::find()
+            .filter(voter_identity::Column::TokenHash.eq(hash_token(token)))
+            .filter(voter_identity::Column::VerifiedAt.is_not_null())
+            .one(db)
+            .await?;
+
+        Ok(identity)
+    }
+
+    /// Marks the challenge as passed, the pending token becomes the token of
+    /// the identity
+    pub async fn confirm(self, db: &DatabaseConnection) -> ModelResult<Self> {
+        let pending_token_hash = self.pending_token_hash.clone();
+
+        let mut identity = self.into_active_model();
+        identity.token_hash = ActiveValue::set(pending_token_hash);
+        identity.pending_token_hash = ActiveValue::set(None);
+        identity.claimed_at = ActiveValue::set(None);
+        identity.code = ActiveValue::set(None);
+        identity.verified_at = ActiveValue::set(Some(Utc::now().into()));
+
+        Ok(identity.update(db).await?)
+    }
+}
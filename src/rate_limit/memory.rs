@@ -0,0 +1,68 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::{refill_time, take_token, RateLimitError, RateLimitStore};
+use crate::common::settings::RateLimitRule;
+
+/// Buckets are only pruned once there are this many
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    /// When the bucket is full again and can be dropped
+    full_at: Instant,
+}
+
+/// Keeps the buckets in process
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> Result<Option<Duration>, RateLimitError> {
+        let now = Instant::now();
+        let mut buckets = self
+            .buckets
+            .lock()
+            .map_err(|err| RateLimitError::StoreNotWorking(err.to_string()))?;
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| bucket.full_at > now);
+        }
+
+        let (tokens, elapsed) = buckets.get(key).map_or_else(
+            || (f64::from(rule.burst), 0.0),
+            |bucket| {
+                (
+                    bucket.tokens,
+                    now.duration_since(bucket.updated_at).as_secs_f64(),
+                )
+            },
+        );
+        let (tokens, wait) = take_token(tokens, elapsed, rule);
+
+        let refill = refill_time(f64::from(rule.burst) - tokens, rule);
+        buckets.insert(
+            key.to_string(),
+            Bucket {
+                tokens,
+                updated_at: now,
+                full_at: now + refill,
+            },
+        );
+
+        Ok(wait)
+    }
+}
@@ -0,0 +1,134 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use loco_rs::prelude::*;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection};
+
+use crate::common::settings::{RateLimitBackend, RateLimitRule, RateLimitSettings};
+
+pub mod memory;
+pub mod postgres;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RateLimitError {
+    #[error("rate limit store not working: {0}")]
+    StoreNotWorking(String),
+}
+
+/// Keeps the token buckets of the clients
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from the bucket at `key`, returns how long until the
+    /// next token when the bucket is empty
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> std::result::Result<Option<Duration>, RateLimitError>;
+}
+
+/// The tokens a bucket holds after refilling it for `elapsed` seconds and
+/// trying to take one, and how long to wait when there was none to take
+pub(crate) fn take_token(
+    tokens: f64,
+    elapsed: f64,
+    rule: &RateLimitRule,
+) -> (f64, Option<Duration>) {
+    let rate = f64::from(rule.per_minute) / 60.0;
+    let tokens = (tokens + elapsed.max(0.0) * rate).min(f64::from(rule.burst));
+
+    if tokens >= 1.0 {
+        return (tokens - 1.0, None);
+    }
+
+    let wait = if rate > 0.0 {
+        Duration::from_secs_f64((1.0 - tokens) / rate)
+    } else {
+        Duration::MAX
+    };
+
+    (tokens, Some(wait))
+}
+
+/// How long a bucket takes to get `missing` tokens back, a rule that never
+/// refills is taken as refilling in an hour
+pub(crate) fn refill_time(missing: f64, rule: &RateLimitRule) -> Duration {
+    if rule.per_minute > 0 {
+        Duration::from_secs_f64(missing.max(0.0) * 60.0 / f64::from(rule.per_minute))
+    } else {
+        Duration::from_secs(60 * 60)
+    }
+}
+
+/// Rate limits requests by route and client address
+#[derive(Clone)]
+pub struct RateLimiter {
+    rules: Arc<Vec<RateLimitRule>>,
+    store: Arc<dyn RateLimitStore>,
+    fail_closed: bool,
+}
+
+impl RateLimiter {
+    pub fn from_settings(settings: &RateLimitSettings, db: &DatabaseConnection) -> Result<Self> {
+        let store: Arc<dyn RateLimitStore> = match settings.backend {
+            RateLimitBackend::Memory => Arc::new(memory::MemoryRateLimitStore::default()),
+            RateLimitBackend::Postgres => {
+                if db.get_database_backend() != DatabaseBackend::Postgres {
+                    return Err(Error::Message(
+                        "the `postgres` rate limit backend needs a Postgres database".to_string(),
+                    ));
+                }
+
+                // a bucket left alone this long is full, same as no bucket
+                let idle = settings
+                    .rules
+                    .iter()
+                    .map(|rule| refill_time(f64::from(rule.burst), rule))
+                    .max()
+                    .unwrap_or_default();
+
+                Arc::new(postgres::PostgresRateLimitStore::new(db.clone(), idle))
+            }
+        };
+
+        Ok(Self {
+            rules: Arc::new(settings.rules.clone()),
+            store,
+            fail_closed: settings.fail_closed,
+        })
+    }
+
+    /// Whether requests are turned away while the store isn't working
+    pub fn fail_closed(&self) -> bool {
+        self.fail_closed
+    }
+
+    /// finds the rule for a route, routes without one aren't limited
+    pub fn rule(&self, method: &str, path: &str) -> Option<&RateLimitRule> {
+        self.rules.iter().find(|rule| {
+            rule.path == path
+                && rule
+                    .method
+                    .as_ref()
+                    .is_none_or(|rule_method| rule_method.eq_ignore_ascii_case(method))
+        })
+    }
+
+    /// Takes a token for the client, returns how long until it may retry when
+    /// it is over the limit. `client` is the hash of the client address, so
+    /// the store never holds addresses.
+    pub async fn check(
+        &self,
+        rule: &RateLimitRule,
+        client: &str,
+    ) -> std::result::Result<Option<Duration>, RateLimitError> {
+        let key = format!(
+            "{} {} {}",
+            rule.method.as_deref().unwrap_or("*"),
+            rule.path,
+            client
+        );
+
+        self.store.take(&key, rule).await
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QuerySelect, TransactionTrait,
+};
+
+use super::{take_token, RateLimitError, RateLimitStore};
+use crate::{common::settings::RateLimitRule, models::_entities::rate_limit_bucket};
+
+impl From<DbErr> for RateLimitError {
+    fn from(err: DbErr) -> Self {
+        Self::StoreNotWorking(err.to_string())
+    }
+}
+
+/// How often an instance drops the buckets that are full again
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps the buckets in the `rate_limit_bucket` table so every instance
+/// shares them, a bucket row is locked while a token is taken
+pub struct PostgresRateLimitStore {
+    db: DatabaseConnection,
+    /// How long a bucket takes to fill up under the slowest rule
+    idle: Duration,
+    last_pruned: Mutex<Instant>,
+}
+
+impl PostgresRateLimitStore {
+    pub fn new(db: DatabaseConnection, idle: Duration) -> Self {
+        Self {
+            db,
+            idle,
+            last_pruned: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Drops the buckets no one took a token from in `idle`, they are full
+    /// again. Returns how many were dropped.
+    pub async fn prune(&self) -> Result<u64, RateLimitError> {
+        let idle = chrono::Duration::from_std(self.idle)
+            .map_err(|err| RateLimitError::StoreNotWorking(err.to_string()))?;
+        let before: DateTime<FixedOffset> = (Utc::now() - idle).into();
+
+        Ok(rate_limit_bucket::Entity::delete_many()
+            .filter(rate_limit_bucket::Column::UpdatedAt.lt(before))
+            .exec(&self.db)
+            .await?
+            .rows_affected)
+    }
+
+    fn prune_due(&self) -> Result<bool, RateLimitError> {
+        let mut last_pruned = self
+            .last_pruned
+            .lock()
+            .map_err(|err| RateLimitError::StoreNotWorking(err.to_string()))?;
+
+        if last_pruned.elapsed() < PRUNE_INTERVAL {
+            return Ok(false);
+        }
+        *last_pruned = Instant::now();
+
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        rule: &RateLimitRule,
+    ) -> Result<Option<Duration>, RateLimitError> {
+        let now: DateTime<FixedOffset> = Utc::now().into();
+
+        let txn = self.db.begin().await?;
+
+        rate_limit_bucket::Entity::insert(rate_limit_bucket::ActiveModel {
+            key: ActiveValue::set(key.to_string()),
+            tokens: ActiveValue::set(f64::from(rule.burst)),
+            updated_at: ActiveValue::set(now),
+        })
+        .on_conflict(
+            OnConflict::column(rate_limit_bucket::Column::Key)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+
+        let bucket = rate_limit_bucket::Entity::find_by_id(key)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| RateLimitError::StoreNotWorking(format!("bucket {} vanished", key)))?;
+
+        let elapsed = (now - bucket.updated_at).num_milliseconds() as f64 / 1000.0;
+        let (tokens, wait) = take_token(bucket.tokens, elapsed, rule);
+
+        rate_limit_bucket::ActiveModel {
+            key: ActiveValue::unchanged(bucket.key),
+            tokens: ActiveValue::set(tokens),
+            updated_at: ActiveValue::set(now),
+        }
+        .update(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        if self.prune_due()? {
+            self.prune().await?;
+        }
+
+        Ok(wait)
+    }
+}
@@ -0,0 +1,165 @@
+use axum::http::{header, HeaderMap};
+
+use crate::common::settings::RiskSettings;
+
+/// Weight of a request without a user agent
+const NO_USER_AGENT: f32 = 0.3;
+/// Weight of a user agent of an HTTP library or headless browser
+const SCRIPTED_USER_AGENT: f32 = 0.4;
+/// Weight of a request without `accept-language`, browsers always send it
+const NO_ACCEPT_LANGUAGE: f32 = 0.15;
+/// Weight of a request without `origin` or `referer`, browsers send one on
+/// every `POST`
+const NO_ORIGIN: f32 = 0.1;
+/// Weight of an address that already voted a lot recently
+const BUSY_ADDRESS: f32 = 0.3;
+/// Weight of a network that already voted a lot recently
+const BUSY_NETWORK: f32 = 0.2;
+/// Weight of a captcha score of 0, scaled down as the score goes up
+const CAPTCHA_SCORE: f32 = 0.5;
+
+/// Words user agents of HTTP libraries and headless browsers start with
+const SCRIPTED_AGENTS: &[&str] = &[
+    "curl",
+    "wget",
+    "python",
+    "go-http-client",
+    "java",
+    "okhttp",
+    "node-fetch",
+    "axios",
+    "libwww",
+    "scrapy",
+    "headless",
+    "phantomjs",
+    "selenium",
+    "puppeteer",
+];
+
+/// Endings of the product names of crawlers, e.g. `Googlebot/2.1`
+const CRAWLER_PRODUCTS: &[&str] = &["bot", "spider", "crawler"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    /// The vote goes through
+    Low,
+    /// The vote needs the extra challenge
+    Medium,
+    /// The vote is rejected or shadow-recorded
+    High,
+}
+
+/// What a vote request is scored on
+#[derive(Debug, Clone, Default)]
+pub struct RiskSignals {
+    pub user_agent: Option<String>,
+    pub accept_language: bool,
+    /// The request has an `origin` or `referer`
+    pub origin: bool,
+    /// Votes from the address in the window
+    pub address_votes: u64,
+    /// Votes from the /24 or /48 of the address in the window
+    pub network_votes: u64,
+    /// Score of the captcha, when the provider returns one
+    pub captcha_score: Option<f32>,
+}
+
+impl RiskSignals {
+    /// Reads the header signals, the others are left empty
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|header| header.to_str().ok())
+            .map(str::trim)
+            .filter(|user_agent| !user_agent.is_empty())
+            .map(ToString::to_string);
+
+        Self {
+            user_agent,
+            accept_language: headers.contains_key(header::ACCEPT_LANGUAGE),
+            origin: headers.contains_key(header::ORIGIN) || headers.contains_key(header::REFERER),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskAssessment {
+    pub score: f32,
+    pub level: RiskLevel,
+    /// What added to the score
+    pub reasons: Vec<&'static str>,
+}
+
+/// Whether the user agent is an HTTP library, headless browser or crawler.
+/// Only whole words and product names are matched, so e.g. a `CUBOT` phone
+/// isn't taken for a bot.
+pub fn is_scripted(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_lowercase();
+
+    let scripted = user_agent
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .any(|word| SCRIPTED_AGENTS.iter().any(|agent| word.starts_with(agent)));
+    let crawler = user_agent
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '(' | ')' | ','))
+        .filter_map(|product| product.split_once('/').map(|(name, _)| name))
+        .any(|name| {
+            CRAWLER_PRODUCTS
+                .iter()
+                .any(|crawler| name.ends_with(crawler))
+        });
+
+    scripted || crawler
+}
+
+/// Adds up the weights of the signals found, capped at 1
+pub fn assess(settings: &RiskSettings, signals: &RiskSignals) -> RiskAssessment {
+    let mut score: f32 = 0.0;
+    let mut reasons = Vec::new();
+    let mut add = |weight: f32, reason: &'static str| {
+        score += weight;
+        reasons.push(reason);
+    };
+
+    match &signals.user_agent {
+        None => add(NO_USER_AGENT, "no user agent"),
+        Some(user_agent) => {
+            if is_scripted(user_agent) {
+                add(SCRIPTED_USER_AGENT, "scripted user agent");
+            }
+        }
+    }
+    if !signals.accept_language {
+        add(NO_ACCEPT_LANGUAGE, "no accept-language");
+    }
+    if !signals.origin {
+        add(NO_ORIGIN, "no origin");
+    }
+    if signals.address_votes >= u64::from(settings.address_votes) {
+        add(BUSY_ADDRESS, "busy address");
+    }
+    if signals.network_votes >= u64::from(settings.network_votes) {
+        add(BUSY_NETWORK, "busy network");
+    }
+    if let Some(captcha_score) = signals.captcha_score {
+        let weight = CAPTCHA_SCORE * (1.0 - captcha_score.clamp(0.0, 1.0));
+        if weight > 0.0 {
+            add(weight, "low captcha score");
+        }
+    }
+
+    let score = score.min(1.0);
+    let level = if score >= settings.high_score {
+        RiskLevel::High
+    } else if score >= settings.challenge_score {
+        RiskLevel::Medium
+    } else {
+        RiskLevel::Low
+    };
+
+    RiskAssessment {
+        score,
+        level,
+        reasons,
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::BTreeMap;
+
+use loco_rs::prelude::*;
+
+use super::required_var;
+use crate::models::_entities::user;
+
+pub struct BanUsername;
+
+#[async_trait]
+impl Task for BanUsername {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "ban_username".to_string(),
+            detail: "Delete a user and all the votes it received (username:<username>)".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &BTreeMap<String, String>) -> Result<()> {
+        let username = required_var(vars, "username")?.to_lowercase();
+
+        let votes = user::Model::delete_with_votes(&app_context.db, &username).await?;
+
+        println!("deleted {} and its {} votes", username, votes);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use loco_rs::prelude::*;
+use sea_orm::EntityTrait;
+
+use crate::{
+    anomaly,
+    common::settings::Settings,
+    models::_entities::{season, vote_flag, voter},
+};
+
+pub struct DetectAnomalies;
+
+#[async_trait]
+impl Task for DetectAnomalies {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "detect_anomalies".to_string(),
+            detail: "Flag suspicious votes of the active season or another one ([season:<id>])"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &BTreeMap<String, String>) -> Result<()> {
+        let settings = Settings::from_context(app_context)?;
+
+        let season = match vars.get("season") {
+            Some(id) => {
+                let id = id
+                    .parse::<i32>()
+                    .map_err(|_| Error::Message("`season` must be a season id".to_string()))?;
+                season::Entity::find_by_id(id).one(&app_context.db).await?
+            }
+            None => season::Model::find_active(&app_context.db).await?,
+        }
+        .ok_or_else(|| Error::Message("season not found".to_string()))?;
+
+        let votes = voter::Model::find_samples(&app_context.db, season.id).await?;
+        let flags = anomaly::detect(&settings.anomaly, &votes);
+        let recorded = vote_flag::Model::record(&app_context.db, &flags).await?;
+
+        println!(
+            "scanned {} votes of {}: {} flags, {} new",
+            votes.len(),
+            season.name,
+            flags.len(),
+            recorded
+        );
+
+        if settings.anomaly.shadow {
+            let mut ids: Vec<i32> = flags.iter().map(|flag| flag.voter_id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+
+            let shadowed = voter::Model::set_shadow(&app_context.db, &ids, true).await?;
+            println!("shadowed {} votes", shadowed);
+        }
+
+        Ok(())
+    }
+}
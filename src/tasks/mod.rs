@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use loco_rs::prelude::*;
+
+pub mod ban_username;
+pub mod detect_anomalies;
+pub mod prune_orphans;
+pub mod purge_voters;
+pub mod start_season;
+pub mod stats;
+
+/// Gets a `name:value` variable passed to `cargo loco task`
+fn required_var<'a>(vars: &'a BTreeMap<String, String>, name: &str) -> Result<&'a str> {
+    vars.get(name)
+        .map(String::as_str)
+        .ok_or_else(|| Error::Message(format!("missing `{}:<value>`", name)))
+}
+
+/// Accepts RFC 3339 timestamps or plain dates (midnight UTC)
+fn parse_date(name: &str, date: &str) -> Result<DateTime<FixedOffset>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date);
+    }
+
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc().into())
+        .map_err(|err| Error::Message(format!("invalid `{}`: {}", name, err)))
+}
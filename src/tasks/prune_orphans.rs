@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+use loco_rs::prelude::*;
+
+use crate::models::_entities::user;
+
+pub struct PruneOrphans;
+
+#[async_trait]
+impl Task for PruneOrphans {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "prune_orphans".to_string(),
+            detail: "Delete users with zero votes".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &BTreeMap<String, String>) -> Result<()> {
+        let deleted = user::Model::prune_orphans(&app_context.db).await?;
+
+        println!("deleted {} users", deleted);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use ipnet::IpNet;
+use loco_rs::prelude::*;
+
+use super::parse_date;
+use crate::{models::_entities::voter, utils::address_hash::AddressHasher};
+
+pub struct PurgeVoters;
+
+#[async_trait]
+impl Task for PurgeVoters {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "purge_voters".to_string(),
+            detail: "Delete voters created before a date and/or in a CIDR (before:2024-03-01 \
+                     cidr:10.0.0.0/16)"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &BTreeMap<String, String>) -> Result<()> {
+        let before = vars
+            .get("before")
+            .map(|date| parse_date("before", date))
+            .transpose()?;
+        let hasher = AddressHasher::from_context(app_context)?;
+        let networks = vars
+            .get("cidr")
+            .map(|cidr| {
+                cidr.parse::<IpNet>()
+                    .map_err(|err| err.to_string())
+                    .and_then(|cidr| hasher.stored_networks(cidr).map_err(|err| err.to_string()))
+                    .map_err(|err| Error::Message(format!("invalid `cidr`: {}", err)))
+            })
+            .transpose()?;
+
+        if before.is_none() && networks.is_none() {
+            return Err(Error::Message(
+                "pass `before:<date>` and/or `cidr:<network>`".to_string(),
+            ));
+        }
+
+        let deleted = voter::Model::purge(&app_context.db, before, networks.as_deref()).await?;
+
+        println!("deleted {} voters", deleted);
+
+        Ok(())
+    }
+}
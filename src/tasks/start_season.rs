@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use loco_rs::prelude::*;
+
+use super::{parse_date, required_var};
+use crate::models::_entities::season;
+
+pub struct StartSeason;
+
+#[async_trait]
+impl Task for StartSeason {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "start_season".to_string(),
+            detail: "End the running season and start a new one (name:<name> \
+                     [starts:2024-05-01])"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &BTreeMap<String, String>) -> Result<()> {
+        let name = required_var(vars, "name")?;
+        let starts_at = match vars.get("starts") {
+            Some(date) => parse_date("starts", date)?,
+            None => Utc::now().into(),
+        };
+
+        let season = season::Model::start(&app_context.db, name, starts_at).await?;
+
+        println!(
+            "started season {} ({}) at {}",
+            season.name,
+            season.id,
+            season.starts_at.to_rfc3339()
+        );
+
+        Ok(())
+    }
+}
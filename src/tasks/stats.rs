@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use loco_rs::prelude::*;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+use crate::models::{
+    _entities::{season, user, voter},
+    user::LeaderboardWindow,
+    voter::VoteStatus,
+};
+
+const DEFAULT_TOP: u64 = 10;
+
+pub struct Stats;
+
+#[async_trait]
+impl Task for Stats {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "stats".to_string(),
+            detail: "Print user and vote totals and the top N users of the latest season (top:10)"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &BTreeMap<String, String>) -> Result<()> {
+        let top = match vars.get("top") {
+            Some(top) => top
+                .parse()
+                .map_err(|_| Error::Message("`top` must be a positive number".to_string()))?,
+            None => DEFAULT_TOP,
+        };
+
+        let users = user::Entity::find().count(&app_context.db).await?;
+        let votes = voter::Entity::find()
+            .filter(voter::Column::Status.eq(VoteStatus::Confirmed.as_str()))
+            .count(&app_context.db)
+            .await?;
+
+        println!("users: {}", users);
+        println!("votes: {}", votes);
+
+        if top == 0 {
+            return Ok(());
+        }
+
+        let season = season::Model::find_latest(&app_context.db).await?;
+        match &season {
+            Some(season) => println!("top {} of {}:", top, season.name),
+            None => println!("top {}:", top),
+        }
+        let leaderboard = user::Model::find_leaderboard(
+            &app_context.db,
+            &None,
+            season.map(|season| season.id),
+            LeaderboardWindow::All,
+            1,
+            top,
+        )
+        .await?;
+        for user in leaderboard {
+            println!("{:>4}. {} ({})", user.rank, user.username, user.votes);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use std::net::IpAddr;
+
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
+use loco_rs::prelude::*;
+use sha2::Sha256;
+
+use crate::common::settings::Settings;
+
+/// Prefix length the stored network of IPv4 voters is truncated to
+const IPV4_NETWORK_PREFIX: u8 = 24;
+/// Prefix length the stored network of IPv6 voters is truncated to
+const IPV6_NETWORK_PREFIX: u8 = 48;
+/// A CIDR filter may span at most 2^12 stored networks, e.g. a /12 in IPv4
+const MAX_FILTER_BITS: u8 = 12;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NetworkFilterError {
+    #[error("CIDR is narrower than the stored /{0} networks")]
+    TooNarrow(u8),
+
+    #[error("CIDR spans more than {0} stored networks")]
+    TooWide(u32),
+}
+
+/// What is stored about the address of a voter instead of the address itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoterAddress {
+    /// HMAC of the address with the current secret
+    pub hash: String,
+    /// HMAC of the address with the secret being rotated out
+    pub previous_hash: Option<String>,
+    /// HMAC of the /24 (IPv4) or /48 (IPv6) the address is in, with the
+    /// current secret. Groups the votes of a network, e.g. to purge it.
+    pub network: Option<String>,
+    /// HMAC of the network with the secret being rotated out
+    pub previous_network: Option<String>,
+}
+
+/// Hashes voter addresses with the `address_hash` secrets
+#[derive(Clone)]
+pub struct AddressHasher {
+    secret: String,
+    previous_secret: Option<String>,
+}
+
+impl AddressHasher {
+    pub fn from_settings(settings: &Settings) -> Result<Self> {
+        let secret = settings
+            .address_hash
+            .secret
+            .clone()
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| {
+                Error::Message("missing `settings.address_hash.secret` in config".to_string())
+            })?;
+        let previous_secret = settings
+            .address_hash
+            .previous_secret
+            .clone()
+            .filter(|secret| !secret.is_empty());
+
+        Ok(Self {
+            secret,
+            previous_secret,
+        })
+    }
+
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        Self::from_settings(&Settings::from_context(ctx)?)
+    }
+
+    pub fn hash(&self, address: &str) -> VoterAddress {
+        let address = normalize(address);
+        let network = network(&address);
+
+        VoterAddress {
+            hash: hmac_hex(&self.secret, &address),
+            previous_hash: self
+                .previous_secret
+                .as_ref()
+                .map(|secret| hmac_hex(secret, &address)),
+            network: network
+                .as_ref()
+                .map(|network| hmac_hex(&self.secret, network)),
+            previous_network: network.as_ref().and_then(|network| {
+                self.previous_secret
+                    .as_ref()
+                    .map(|secret| hmac_hex(secret, network))
+            }),
+        }
+    }
+
+    /// The stored networks a CIDR filter matches, hashed with the current
+    /// and previous secret. Only the /24 (IPv4) or /48 (IPv6) of voters is
+    /// kept, so narrower filters can't be answered.
+    pub fn stored_networks(
+        &self,
+        cidr: IpNet,
+    ) -> std::result::Result<Vec<String>, NetworkFilterError> {
+        let prefix = match cidr {
+            IpNet::V4(_) => IPV4_NETWORK_PREFIX,
+            IpNet::V6(_) => IPV6_NETWORK_PREFIX,
+        };
+
+        if cidr.prefix_len() > prefix {
+            return Err(NetworkFilterError::TooNarrow(prefix));
+        }
+        if prefix - cidr.prefix_len() > MAX_FILTER_BITS {
+            return Err(NetworkFilterError::TooWide(1 << MAX_FILTER_BITS));
+        }
+
+        let secrets = std::iter::once(&self.secret).chain(&self.previous_secret);
+
+        Ok(secrets
+            .flat_map(|secret| {
+                cidr.trunc()
+                    .subnets(prefix)
+                    .into_iter()
+                    .flatten()
+                    .map(|network| hmac_hex(secret, &network.to_string()))
+            })
+            .collect())
+    }
+}
+
+/// The same address is always hashed the same way, however it is written
+fn normalize(address: &str) -> String {
+    let address = address.trim();
+
+    address
+        .parse::<IpAddr>()
+        .map_or_else(|_| address.to_string(), |ip| ip.to_canonical().to_string())
+}
+
+fn hmac_hex(secret: &str, address: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(address.as_bytes());
+
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// The /24 (IPv4) or /48 (IPv6) the address is in, `None` when it isn't an
+/// IP address
+pub fn network(address: &str) -> Option<String> {
+    let ip = address.parse::<IpAddr>().ok()?;
+    let prefix = match ip {
+        IpAddr::V4(_) => IPV4_NETWORK_PREFIX,
+        IpAddr::V6(_) => IPV6_NETWORK_PREFIX,
+    };
+
+    IpNet::new(ip, prefix)
+        .ok()
+        .map(|network| network.trunc().to_string())
+}
@@ -1,27 +1,131 @@
+use std::net::{IpAddr, SocketAddr};
+
 use axum::http::HeaderMap;
 use axum_client_ip::SecureClientIp;
+use ipnet::IpNet;
+use loco_rs::prelude::*;
+
+use crate::common::settings::{ClientIpHeader, ClientIpSettings, Settings};
+
+/// Finds the client address behind the configured trusted proxies
+#[derive(Clone, Debug, Default)]
+pub struct ClientIpResolver {
+    trusted_proxies: Vec<IpNet>,
+    headers: Vec<ClientIpHeader>,
+}
+
+impl ClientIpResolver {
+    pub fn new(settings: &ClientIpSettings) -> Self {
+        Self {
+            trusted_proxies: settings.trusted_proxies.clone(),
+            headers: settings.headers.clone(),
+        }
+    }
+
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        Ok(Self::new(&Settings::from_context(ctx)?.client_ip))
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|network| network.contains(&ip.to_canonical()))
+    }
+
+    /// Gets the client address of a request coming from `peer`. Headers are
+    /// only honored when the peer is a trusted proxy, so clients connecting
+    /// directly can't spoof them.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(&peer) {
+            return peer;
+        }
 
-/// Get the IP address from the request headers (railway.app includes the real
-/// IP in the "x-Envoy-external-Address" or "x-forwarded-for" headers)
-pub fn get_ip(secure_ip: &SecureClientIp, headers: &HeaderMap) -> String {
-    if let Some(ip) = headers
-        .get("x-Envoy-external-Address")
-        .map(|header| header.to_str().ok())
-        .flatten()
-    {
-        return ip.to_string();
+        self.headers
+            .iter()
+            .find_map(|header| match header {
+                ClientIpHeader::Envoy => single_ip(headers, "x-envoy-external-address"),
+                ClientIpHeader::CfConnectingIp => single_ip(headers, "cf-connecting-ip"),
+                ClientIpHeader::XForwardedFor => {
+                    self.walk_hops(header_list(headers, "x-forwarded-for").map(parse_node))
+                }
+                ClientIpHeader::Forwarded => {
+                    self.walk_hops(header_list(headers, "forwarded").map(forwarded_for))
+                }
+            })
+            .unwrap_or(peer)
     }
 
-    if let Some(ip) = headers
-        .get("x-forwarded-for")
-        .map(|header| header.to_str().ok())
-        .flatten()
-        .map(|header| header.split(',').last())
-        .flatten()
-    {
-        return ip.to_string();
+    /// Each proxy appends the address it got the request from, so the client
+    /// is the last hop that isn't one of our proxies
+    fn walk_hops(&self, hops: impl Iterator<Item = Option<IpAddr>>) -> Option<IpAddr> {
+        let hops = hops.collect::<Vec<_>>();
+
+        let mut client = None;
+        for hop in hops.into_iter().rev() {
+            // anything left of an unreadable hop could have been made up
+            let hop = hop?;
+            client = Some(hop);
+
+            if !self.is_trusted(&hop) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+/// Get the IP address of the client, taken from the headers set by trusted
+/// proxies or from the socket address otherwise
+pub fn get_ip(
+    resolver: &ClientIpResolver,
+    secure_ip: &SecureClientIp,
+    headers: &HeaderMap,
+) -> String {
+    resolver
+        .resolve(secure_ip.0, headers)
+        .to_canonical()
+        .to_string()
+}
+
+fn single_ip(headers: &HeaderMap, name: &str) -> Option<IpAddr> {
+    headers
+        .get(name)
+        .and_then(|header| header.to_str().ok())
+        .and_then(parse_node)
+}
+
+/// The comma separated entries of every `name` header, in order
+fn header_list<'a>(headers: &'a HeaderMap, name: &str) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(name)
+        .iter()
+        .flat_map(|header| header.to_str().unwrap_or_default().split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+}
+
+/// Gets the `for=` parameter of a `forwarded` entry
+fn forwarded_for(entry: &str) -> Option<IpAddr> {
+    entry
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+        .and_then(|(_, value)| parse_node(value))
+}
+
+/// Parses `1.2.3.4`, `1.2.3.4:80`, `2001:db8::1` and `"[2001:db8::1]:80"`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(address) = node.parse::<SocketAddr>() {
+        return Some(address.ip());
     }
 
-    let layer_ip = secure_ip.0.to_canonical().to_string();
-    layer_ip
+    node.strip_prefix('[')
+        .and_then(|node| node.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
 }
@@ -1 +1,4 @@
+pub mod address_hash;
 pub mod get_ip;
+pub mod voter_resolver;
+pub mod voter_token;
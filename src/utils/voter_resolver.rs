@@ -0,0 +1,179 @@
+use std::net::IpAddr;
+
+use axum::http::{header, HeaderMap};
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
+use loco_rs::prelude::*;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    common::settings::{CompositeCap, Settings, VoterIdentityMode, VoterIdentitySettings},
+    utils::address_hash::network,
+};
+
+pub const VOTER_COOKIE: &str = "crush_voter";
+/// The voter cookie lasts a year
+const VOTER_COOKIE_MAX_AGE: u64 = 60 * 60 * 24 * 365;
+/// Prefix IPv6 addresses are collapsed to, one host usually gets a whole /64
+const IPV6_VOTER_PREFIX: u8 = 64;
+
+/// How an anonymous voter is told apart from the others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedVoter {
+    /// What the vote is deduplicated on, `None` when the voter has no cookie
+    /// yet in the cookie modes
+    pub key: Option<String>,
+    /// The address votes are counted on for the cap
+    pub cap_address: String,
+    /// Votes allowed per `cap_address`, `None` when unlimited
+    pub cap: Option<u32>,
+    /// `Set-Cookie` value when a voter cookie was issued
+    pub set_cookie: Option<String>,
+}
+
+/// Picks what votes are deduplicated on from `settings.voter_identity`
+#[derive(Clone, Debug)]
+pub struct VoterIdentityResolver {
+    mode: VoterIdentityMode,
+    cookie_secret: Option<String>,
+    per_ip_cap: u32,
+    composite_cap: CompositeCap,
+    cookie_secure: bool,
+}
+
+impl VoterIdentityResolver {
+    pub fn from_settings(settings: &VoterIdentitySettings) -> Result<Self> {
+        let cookie_secret = settings
+            .cookie_secret
+            .clone()
+            .filter(|secret| !secret.is_empty());
+
+        if cookie_secret.is_none()
+            && matches!(
+                settings.mode,
+                VoterIdentityMode::Cookie | VoterIdentityMode::Composite
+            )
+        {
+            return Err(Error::Message(
+                "missing `settings.voter_identity.cookie_secret` in config".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            mode: settings.mode,
+            cookie_secret,
+            per_ip_cap: settings.per_ip_cap,
+            composite_cap: settings.composite_cap,
+            cookie_secure: settings.cookie_secure,
+        })
+    }
+
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        Self::from_settings(&Settings::from_context(ctx)?.voter_identity)
+    }
+
+    /// Resolves the voter at `ip`. With `issue_cookie` voters without a valid
+    /// cookie get a new one, otherwise they have no key in the cookie modes.
+    pub fn resolve(&self, ip: &str, headers: &HeaderMap, issue_cookie: bool) -> ResolvedVoter {
+        match self.mode {
+            VoterIdentityMode::ExactIp => ResolvedVoter {
+                key: Some(ip.to_string()),
+                cap_address: ip.to_string(),
+                cap: None,
+                set_cookie: None,
+            },
+            VoterIdentityMode::IpPrefix => ResolvedVoter {
+                key: Some(collapse(ip)),
+                cap_address: collapse(ip),
+                cap: None,
+                set_cookie: None,
+            },
+            VoterIdentityMode::Cookie | VoterIdentityMode::Composite => {
+                let cap_address = match (self.mode, self.composite_cap) {
+                    (VoterIdentityMode::Composite, CompositeCap::IpPrefix) => collapse(ip),
+                    (VoterIdentityMode::Composite, CompositeCap::Network) => {
+                        network(ip).unwrap_or_else(|| ip.to_string())
+                    }
+                    _ => ip.to_string(),
+                };
+
+                let (voter_id, set_cookie) = match self.voter_cookie(headers) {
+                    Some(voter_id) => (Some(voter_id), None),
+                    None if issue_cookie => {
+                        let voter_id = Uuid::new_v4().simple().to_string();
+                        let set_cookie = format!(
+                            "{}={}.{}; Path=/; Max-Age={}; HttpOnly;{} SameSite=Lax",
+                            VOTER_COOKIE,
+                            voter_id,
+                            self.sign(&voter_id),
+                            VOTER_COOKIE_MAX_AGE,
+                            if self.cookie_secure { " Secure;" } else { "" }
+                        );
+
+                        (Some(voter_id), Some(set_cookie))
+                    }
+                    None => (None, None),
+                };
+
+                ResolvedVoter {
+                    key: voter_id.map(|voter_id| format!("cookie:{}", voter_id)),
+                    cap_address,
+                    cap: Some(self.per_ip_cap),
+                    set_cookie,
+                }
+            }
+        }
+    }
+
+    fn mac(&self, voter_id: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(
+            self.cookie_secret.as_deref().unwrap_or_default().as_bytes(),
+        )
+        .expect("HMAC takes keys of any size");
+        mac.update(voter_id.as_bytes());
+
+        mac
+    }
+
+    fn sign(&self, voter_id: &str) -> String {
+        format!("{:x}", self.mac(voter_id).finalize().into_bytes())
+    }
+
+    /// Gets the voter id of a correctly signed voter cookie
+    fn voter_cookie(&self, headers: &HeaderMap) -> Option<String> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .flat_map(|header| header.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .filter(|(name, _)| *name == VOTER_COOKIE)
+            .filter_map(|(_, value)| value.split_once('.'))
+            .find(|(voter_id, signature)| {
+                decode_hex(signature)
+                    .is_some_and(|signature| self.mac(voter_id).verify_slice(&signature).is_ok())
+            })
+            .map(|(voter_id, _)| voter_id.to_string())
+    }
+}
+
+/// IPv6 addresses are collapsed to their /64, anything else is kept as is
+pub fn collapse(ip: &str) -> String {
+    match ip.trim().parse::<IpAddr>() {
+        Ok(address @ IpAddr::V6(_)) => IpNet::new(address, IPV6_VOTER_PREFIX)
+            .map_or_else(|_| ip.to_string(), |network| network.trunc().to_string()),
+        _ => ip.to_string(),
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,12 @@
+use axum::http::HeaderMap;
+
+/// Header verified voters authenticate with
+pub const VOTER_TOKEN_HEADER: &str = "x-voter-token";
+
+/// Get the voter token from the request headers, if any
+pub fn voter_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(VOTER_TOKEN_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .filter(|token| !token.is_empty())
+}
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{
+    app::REQWEST_CLIENT,
+    common::settings::{CaptchaProvider, CaptchaSettings},
+};
+
+const RECAPTCHA_SITEVERIFY_URL: &str = "https://www.google.com/recaptcha/api/siteverify";
+const HCAPTCHA_SITEVERIFY_URL: &str = "https://api.hcaptcha.com/siteverify";
+const TURNSTILE_SITEVERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CaptchaError {
+    #[error("Captcha provider not working")]
+    ProviderNotWorking(#[from] reqwest::Error),
+
+    #[error("Failed to parse captcha response")]
+    FailedToParse(#[from] serde_json::Error),
+
+    #[error("Captcha failed")]
+    Failed,
+
+    #[error("Captcha secret is not configured")]
+    MissingSecret,
+}
+
+/// The result of a successful captcha verification
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CaptchaVerification {
+    /// Score returned by the provider, only reCAPTCHA v3 (and hCaptcha
+    /// enterprise) return one
+    pub score: Option<f32>,
+}
+
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    /// Verifies a token submitted by the client
+    async fn verify(
+        &self,
+        token: &str,
+        remote_ip: Option<&str>,
+    ) -> Result<CaptchaVerification, CaptchaError>;
+}
+
+/// Builds the verifier for the configured provider
+pub fn from_settings(settings: &CaptchaSettings) -> Result<Arc<dyn CaptchaVerifier>, CaptchaError> {
+    let url = match settings.provider {
+        CaptchaProvider::Fake => {
+            return Ok(Arc::new(FakeCaptchaVerifier {
+                token: settings.fake_token.clone(),
+                score: settings.fake_score,
+            }))
+        }
+        CaptchaProvider::Recaptcha => RECAPTCHA_SITEVERIFY_URL,
+        CaptchaProvider::Hcaptcha => HCAPTCHA_SITEVERIFY_URL,
+        CaptchaProvider::Turnstile => TURNSTILE_SITEVERIFY_URL,
+    };
+
+    let secret = settings
+        .secret
+        .clone()
+        .filter(|secret| !secret.is_empty())
+        .ok_or(CaptchaError::MissingSecret)?;
+
+    Ok(Arc::new(SiteverifyCaptchaVerifier {
+        url,
+        secret,
+        min_score: settings.min_score,
+    }))
+}
+
+/// reCAPTCHA, hCaptcha and Turnstile all share the same siteverify protocol,
+/// only the endpoint differs
+pub struct SiteverifyCaptchaVerifier {
+    url: &'static str,
+    secret: String,
+    min_score: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SiteverifyResponse {
+    success: bool,
+    score: Option<f32>,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+#[async_trait]
+impl CaptchaVerifier for SiteverifyCaptchaVerifier {
+    async fn verify(
+        &self,
+        token: &str,
+        remote_ip: Option<&str>,
+    ) -> Result<CaptchaVerification, CaptchaError> {
+        let mut form = vec![("secret", self.secret.as_str()), ("response", token)];
+        if let Some(remote_ip) = remote_ip {
+            form.push(("remoteip", remote_ip));
+        }
+
+        let request = REQWEST_CLIENT.client.post(self.url).form(&form).build()?;
+
+        let result = REQWEST_CLIENT.client.execute(request).await?.text().await?;
+
+        let result: SiteverifyResponse = serde_json::from_str(&result)?;
+
+        if !result.success {
+            debug!("Captcha rejected: {:?}", result.error_codes);
+            return Err(CaptchaError::Failed);
+        }
+
+        if let (Some(min_score), Some(score)) = (self.min_score, result.score) {
+            if score < min_score {
+                debug!("Captcha score {} is below {}", score, min_score);
+                return Err(CaptchaError::Failed);
+            }
+        }
+
+        Ok(CaptchaVerification {
+            score: result.score,
+        })
+    }
+}
+
+/// Accepts tokens without calling out, so the vote flow can run offline
+pub struct FakeCaptchaVerifier {
+    token: Option<String>,
+    score: Option<f32>,
+}
+
+#[async_trait]
+impl CaptchaVerifier for FakeCaptchaVerifier {
+    async fn verify(
+        &self,
+        token: &str,
+        _remote_ip: Option<&str>,
+    ) -> Result<CaptchaVerification, CaptchaError> {
+        let accepted = match &self.token {
+            Some(expected) => expected == token,
+            None => !token.is_empty(),
+        };
+
+        if !accepted {
+            return Err(CaptchaError::Failed);
+        }
+
+        Ok(CaptchaVerification { score: self.score })
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use loco_rs::prelude::*;
+use sea_orm::DatabaseConnection;
+use tokio::sync::OnceCell;
+
+use crate::common::settings::{IpListPolicy, Settings};
+
+pub mod captcha;
+pub mod username;
+pub mod username_cache;
+
+static SHARED: OnceCell<Verifiers> = OnceCell::const_new();
+
+/// Verifiers built once from [`Settings`] and shared with the handlers
+/// through an axum `Extension` and with the workers
+#[derive(Clone)]
+pub struct Verifiers {
+    pub captcha: Arc<dyn captcha::CaptchaVerifier>,
+    /// Verifies the extra challenge of risky votes, set whenever a vote can
+    /// be asked for it
+    pub challenge: Option<Arc<dyn captcha::CaptchaVerifier>>,
+    pub username: Arc<dyn username::UsernameVerifier>,
+}
+
+impl Verifiers {
+    /// Returns the verifiers of this process, building them on first use so
+    /// handlers and workers share the same caches
+    pub async fn shared(ctx: &AppContext) -> Result<Self> {
+        let verifiers = SHARED
+            .get_or_try_init(|| async {
+                Self::from_settings(&Settings::from_context(ctx)?, &ctx.db)
+            })
+            .await?;
+
+        Ok(verifiers.clone())
+    }
+
+    pub fn from_settings(settings: &Settings, db: &DatabaseConnection) -> Result<Self> {
+        let captcha = captcha::from_settings(&settings.captcha)
+            .map_err(|err| Error::Message(format!("could not build captcha verifier: {}", err)))?;
+        let challenge = match &settings.risk.challenge {
+            Some(challenge) => {
+                // the challenge is shown after the captcha was solved, it
+                // has to be another one
+                if challenge.provider == settings.captcha.provider
+                    && challenge.secret == settings.captcha.secret
+                    && challenge.fake_token == settings.captcha.fake_token
+                {
+                    return Err(Error::Message(
+                        "`settings.risk.challenge` must differ from `settings.captcha`".to_string(),
+                    ));
+                }
+
+                Some(captcha::from_settings(challenge).map_err(|err| {
+                    Error::Message(format!("could not build challenge verifier: {}", err))
+                })?)
+            }
+            None if needs_challenge(settings) => {
+                return Err(Error::Message(
+                    "missing `settings.risk.challenge` in config, risk scoring or an IP list \
+                     asks for the extra challenge"
+                        .to_string(),
+                ));
+            }
+            None => None,
+        };
+        let mut username = username::from_settings(&settings.username_verifier)
+            .map_err(|err| Error::Message(format!("could not build username verifier: {}", err)))?;
+
+        if settings.username_cache.enable {
+            username = Arc::new(username_cache::CachedUsernameVerifier::new(
+                username,
+                db.clone(),
+                &settings.username_cache,
+            ));
+        }
+
+        Ok(Self {
+            captcha,
+            challenge,
+            username,
+        })
+    }
+}
+
+/// Whether a vote can be asked for the extra challenge
+fn needs_challenge(settings: &Settings) -> bool {
+    settings.risk.enable
+        || settings
+            .ip_reputation
+            .lists
+            .iter()
+            .any(|list| list.policy == IpListPolicy::Challenge)
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    app::REQWEST_CLIENT,
+    common::settings::{UsernameVerifierBackend, UsernameVerifierSettings},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum UsernameVerifierError {
+    #[error("Threads not working")]
+    ThreadsNotWorking(#[from] reqwest::Error),
+
+    #[error("Could not read username fixture: {0}")]
+    FixtureUnreadable(#[from] std::io::Error),
+
+    #[error("Invalid username fixture: {0}")]
+    FixtureInvalid(#[from] serde_yaml::Error),
+
+    #[error("`fixture_path` is required by the fixture backend")]
+    MissingFixturePath,
+
+    #[error("Threads lookup failed recently")]
+    LookupFailed,
+}
+
+#[async_trait]
+pub trait UsernameVerifier: Send + Sync {
+    /// Checks if the (lowercase) username exists on threads
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError>;
+
+    /// Fetches the bio of the (lowercase) username, `None` when it does not
+    /// exist. Never cached, bios change.
+    async fn fetch_profile(&self, username: &str) -> Result<Option<String>, UsernameVerifierError>;
+}
+
+/// Outcome of looking for a challenge code on a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileCode {
+    Found,
+    Missing,
+    ProfileNotFound,
+}
+
+/// Checks that the bio of `username` shows `code`, which proves the
+/// requester controls the account
+pub async fn find_profile_code(
+    verifier: &dyn UsernameVerifier,
+    username: &str,
+    code: &str,
+) -> Result<ProfileCode, UsernameVerifierError> {
+    let result = match verifier.fetch_profile(username).await? {
+        Some(bio) if bio.contains(code) => ProfileCode::Found,
+        Some(_) => ProfileCode::Missing,
+        None => ProfileCode::ProfileNotFound,
+    };
+
+    Ok(result)
+}
+
+/// Builds the verifier for the configured backend
+pub fn from_settings(
+    settings: &UsernameVerifierSettings,
+) -> Result<Arc<dyn UsernameVerifier>, UsernameVerifierError> {
+    let verifier: Arc<dyn UsernameVerifier> = match settings.backend {
+        UsernameVerifierBackend::Threads => Arc::new(ThreadsUsernameVerifier),
+        UsernameVerifierBackend::Allowlist => Arc::new(AllowlistUsernameVerifier::new(
+            settings.allowlist.iter().map(String::as_str),
+        )),
+        UsernameVerifierBackend::Fixture => {
+            let path = settings
+                .fixture_path
+                .as_ref()
+                .ok_or(UsernameVerifierError::MissingFixturePath)?;
+
+            Arc::new(FixtureUsernameVerifier::from_file(path)?)
+        }
+    };
+
+    Ok(verifier)
+}
+
+/// Looks the profile page up on threads.net
+pub struct ThreadsUsernameVerifier;
+
+#[async_trait]
+impl UsernameVerifier for ThreadsUsernameVerifier {
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError> {
+        Ok(self.fetch_profile(username).await?.is_some())
+    }
+
+    async fn fetch_profile(&self, username: &str) -> Result<Option<String>, UsernameVerifierError> {
+        let request = REQWEST_CLIENT
+            .client
+            .get(format!("https://threads.net/@{}", username))
+            .build()?;
+        let result = REQWEST_CLIENT.client.execute(request).await?.text().await?;
+
+        if !result.contains(username) {
+            return Ok(None);
+        }
+
+        Ok(Some(profile_bio(&result).unwrap_or_default()))
+    }
+}
+
+/// Gets the bio out of a threads profile page, from its `og:description`
+/// meta tag. The rest of the page shows replies and other profiles, a code
+/// there proves nothing.
+pub fn profile_bio(page: &str) -> Option<String> {
+    let at = page.find(r#"property="og:description""#)?;
+    let start = page[..at].rfind("<meta")?;
+    let end = at + page[at..].find('>')?;
+
+    let (_, content) = page[start..end].split_once(r#"content=""#)?;
+    let content = &content[..content.find('"')?];
+
+    Some(
+        content
+            .replace("&quot;", "\"")
+            .replace("&#x27;", "'")
+            .replace("&#39;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// Accepts every username in the list, or everything when the list is empty
+pub struct AllowlistUsernameVerifier {
+    usernames: HashSet<String>,
+}
+
+impl AllowlistUsernameVerifier {
+    pub fn new<'a>(usernames: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            usernames: usernames.into_iter().map(str::to_lowercase).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl UsernameVerifier for AllowlistUsernameVerifier {
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError> {
+        Ok(self.usernames.is_empty() || self.usernames.contains(username))
+    }
+
+    /// Accepted usernames have an empty bio, so challenges never pass
+    async fn fetch_profile(&self, username: &str) -> Result<Option<String>, UsernameVerifierError> {
+        Ok(self.exists(username).await?.then(String::new))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct FixtureProfile {
+    username: String,
+    #[serde(default)]
+    bio: String,
+}
+
+/// Accepts the profiles listed in a YAML fixture file, e.g.
+///
+/// ```yaml
+/// - username: zuck
+///   bio: Building the future
+/// - username: mosseri
+/// ```
+pub struct FixtureUsernameVerifier {
+    profiles: HashMap<String, String>,
+}
+
+impl FixtureUsernameVerifier {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, UsernameVerifierError> {
+        let profiles: Vec<FixtureProfile> = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+
+        Ok(Self {
+            profiles: profiles
+                .into_iter()
+                .map(|profile| (profile.username.to_lowercase(), profile.bio))
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl UsernameVerifier for FixtureUsernameVerifier {
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError> {
+        Ok(self.profiles.contains_key(username))
+    }
+
+    async fn fetch_profile(&self, username: &str) -> Result<Option<String>, UsernameVerifierError> {
+        Ok(self.profiles.get(username).cloned())
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::Duration;
+use sea_orm::DatabaseConnection;
+use tokio::sync::OnceCell;
+use tracing::{error, warn};
+
+use super::username::{UsernameVerifier, UsernameVerifierError};
+use crate::{
+    common::settings::UsernameCacheSettings,
+    models::{_entities::username_verification, username_verification::VerificationStatus},
+};
+
+/// Wraps another verifier, reusing results stored in the
+/// `username_verification` table until they expire. Concurrent lookups of
+/// the same username share a single call to the wrapped verifier.
+pub struct CachedUsernameVerifier {
+    inner: Arc<dyn UsernameVerifier>,
+    db: DatabaseConnection,
+    ttl: Duration,
+    error_ttl: Duration,
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<VerificationStatus>>>>,
+}
+
+impl CachedUsernameVerifier {
+    pub fn new(
+        inner: Arc<dyn UsernameVerifier>,
+        db: DatabaseConnection,
+        settings: &UsernameCacheSettings,
+    ) -> Self {
+        Self {
+            inner,
+            db,
+            ttl: Duration::seconds(settings.ttl as i64),
+            error_ttl: Duration::seconds(settings.error_ttl as i64),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the stored result if it has not expired yet
+    async fn cached(&self, username: &str) -> Option<VerificationStatus> {
+        let verification =
+            match username_verification::Model::find_by_username(&self.db, username).await {
+                Ok(verification) => verification?,
+                Err(err) => {
+                    warn!("Could not read username verification cache: {}", err);
+                    return None;
+                }
+            };
+
+        let status = verification.status()?;
+        let ttl = match status {
+            VerificationStatus::Error => self.error_ttl,
+            _ => self.ttl,
+        };
+
+        verification.is_fresh(ttl).then_some(status)
+    }
+
+    /// Asks the wrapped verifier and stores the result
+    async fn lookup(&self, username: &str) -> VerificationStatus {
+        let status = match self.inner.exists(username).await {
+            Ok(true) => VerificationStatus::Exists,
+            Ok(false) => VerificationStatus::NotFound,
+            Err(err) => {
+                error!("Username lookup for {} failed: {}", username, err);
+                VerificationStatus::Error
+            }
+        };
+
+        if let Err(err) = username_verification::Model::record(&self.db, username, status).await {
+            warn!("Could not write username verification cache: {}", err);
+        }
+
+        status
+    }
+}
+
+#[async_trait]
+impl UsernameVerifier for CachedUsernameVerifier {
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError> {
+        let status = match self.cached(username).await {
+            Some(status) => status,
+            None => {
+                let cell = self
+                    .in_flight
+                    .lock()
+                    .unwrap()
+                    .entry(username.to_string())
+                    .or_default()
+                    .clone();
+
+                let status = *cell.get_or_init(|| self.lookup(username)).await;
+
+                let mut in_flight = self.in_flight.lock().unwrap();
+                if in_flight
+                    .get(username)
+                    .is_some_and(|current| Arc::ptr_eq(current, &cell))
+                {
+                    in_flight.remove(username);
+                }
+
+                status
+            }
+        };
+
+        match status {
+            VerificationStatus::Exists => Ok(true),
+            VerificationStatus::NotFound => Ok(false),
+            VerificationStatus::Error => Err(UsernameVerifierError::LookupFailed),
+        }
+    }
+
+    async fn fetch_profile(&self, username: &str) -> Result<Option<String>, UsernameVerifierError> {
+        self.inner.fetch_profile(username).await
+    }
+}
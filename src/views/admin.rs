@@ -0,0 +1,139 @@
+use serde::Serialize;
+
+use super::leaderboard::Pagination;
+use crate::models::{
+    _entities::{blocked_username, report, user, voter},
+    report::ReportStatus,
+    user::UserSummary,
+    vote_flag::{FlagKind, FlaggedUser},
+    voter::VoteStatus,
+};
+
+#[derive(Serialize, Default)]
+pub struct AdminUsersResponse {
+    pub pagination: Pagination,
+    pub users: Vec<AdminUser>,
+}
+
+#[derive(Serialize, Default)]
+pub struct AdminUser {
+    pub id: i32,
+    pub username: String,
+    /// Every vote the user got, whatever its status
+    pub votes: i64,
+    pub suspended: bool,
+    pub created_at: String,
+}
+
+/// Addresses are only stored hashed, the hash of the network still shows
+/// which votes came from the same one
+#[derive(Serialize)]
+pub struct AdminVote {
+    pub id: i32,
+    pub voted_user: Option<String>,
+    pub status: VoteStatus,
+    pub network: Option<String>,
+    pub identity_id: Option<i32>,
+    pub season_id: Option<i32>,
+    /// Recorded but left out of the leaderboard
+    pub shadow: bool,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct AdminReportsResponse {
+    pub pagination: Pagination,
+    pub reports: Vec<AdminReport>,
+}
+
+#[derive(Serialize)]
+pub struct AdminReport {
+    pub id: i32,
+    pub username: Option<String>,
+    pub reason: String,
+    pub status: ReportStatus,
+    /// What the moderator did, set once the report is closed
+    pub resolution: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FlaggedUserResponse {
+    pub username: String,
+    /// Votes not counted until the flags are dismissed
+    pub flagged_votes: u64,
+    pub kinds: Vec<FlagKind>,
+}
+
+#[derive(Serialize, Default)]
+pub struct BlockedUsernameResponse {
+    pub username: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+impl From<UserSummary> for AdminUser {
+    fn from(user: UserSummary) -> Self {
+        AdminUser {
+            id: user.id,
+            username: user.username,
+            votes: user.votes,
+            suspended: user.suspended,
+            created_at: user.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<(voter::Model, Option<user::Model>)> for AdminVote {
+    fn from((voter, user): (voter::Model, Option<user::Model>)) -> Self {
+        AdminVote {
+            status: voter.status(),
+            id: voter.id,
+            voted_user: user.map(|user| user.username),
+            network: voter.network,
+            identity_id: voter.identity_id,
+            season_id: voter.season_id,
+            shadow: voter.shadow,
+            created_at: voter.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<(report::Model, Option<user::Model>)> for AdminReport {
+    fn from((report, user): (report::Model, Option<user::Model>)) -> Self {
+        AdminReport {
+            status: report.status(),
+            id: report.id,
+            username: user.map(|user| user.username),
+            reason: report.reason,
+            resolution: report.resolution,
+            note: report.note,
+            created_at: report.created_at.to_rfc3339(),
+            resolved_at: report
+                .resolved_at
+                .map(|resolved_at| resolved_at.to_rfc3339()),
+        }
+    }
+}
+
+impl From<FlaggedUser> for FlaggedUserResponse {
+    fn from(user: FlaggedUser) -> Self {
+        FlaggedUserResponse {
+            username: user.username,
+            flagged_votes: user.flagged_votes,
+            kinds: user.kinds,
+        }
+    }
+}
+
+impl From<blocked_username::Model> for BlockedUsernameResponse {
+    fn from(blocked: blocked_username::Model) -> Self {
+        BlockedUsernameResponse {
+            username: blocked.username,
+            reason: blocked.reason,
+            created_at: blocked.created_at.to_rfc3339(),
+        }
+    }
+}
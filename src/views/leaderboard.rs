@@ -1,9 +1,11 @@
 use serde::Serialize;
 
-use crate::models::user::UserWithVotes;
+use super::season::SeasonResponse;
+use crate::models::{_entities::season, user::UserWithVotes};
 
 #[derive(Serialize, Default)]
 pub struct LeaderboardResponse {
+    pub season: SeasonResponse,
     pub pagination: Pagination,
     pub users: Vec<User>,
 }
@@ -18,15 +20,20 @@ pub struct Pagination {
 #[derive(Serialize, Default)]
 pub struct User {
     username: String,
-    votes: i64,
+    /// `None` when the owner hides the count
+    votes: Option<i64>,
     rank: i64,
 }
 
 impl LeaderboardResponse {
-    pub fn new(users: Vec<UserWithVotes>, pagination: Pagination) -> Self {
+    pub fn new(season: season::Model, users: Vec<UserWithVotes>, pagination: Pagination) -> Self {
         let users = users.into_iter().map(|user| user.into()).collect();
 
-        LeaderboardResponse { pagination, users }
+        LeaderboardResponse {
+            season: season.into(),
+            pagination,
+            users,
+        }
     }
 }
 
@@ -34,7 +41,7 @@ impl From<UserWithVotes> for User {
     fn from(user: UserWithVotes) -> Self {
         User {
             username: user.username,
-            votes: user.votes,
+            votes: (!user.hide_count).then_some(user.votes),
             rank: user.rank,
         }
     }
@@ -1 +1,4 @@
+pub mod admin;
 pub mod leaderboard;
+pub mod season;
+pub mod user;
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+use crate::models::{_entities::season, user::UserWithVotes};
+
+#[derive(Serialize, Default)]
+pub struct SeasonResponse {
+    pub id: i32,
+    pub name: String,
+    pub starts_at: String,
+    pub ends_at: Option<String>,
+    /// Only the active season takes votes
+    pub active: bool,
+}
+
+#[derive(Serialize, Default)]
+pub struct HallOfFameEntry {
+    pub season: SeasonResponse,
+    /// `None` when nobody got a confirmed vote that season
+    pub winner: Option<Winner>,
+}
+
+#[derive(Serialize, Default)]
+pub struct Winner {
+    pub username: String,
+    /// `None` when the owner hides the count
+    pub votes: Option<i64>,
+}
+
+impl From<season::Model> for SeasonResponse {
+    fn from(season: season::Model) -> Self {
+        SeasonResponse {
+            active: season.is_active(),
+            id: season.id,
+            name: season.name,
+            starts_at: season.starts_at.to_rfc3339(),
+            ends_at: season.ends_at.map(|ends_at| ends_at.to_rfc3339()),
+        }
+    }
+}
+
+impl HallOfFameEntry {
+    pub fn new(season: season::Model, winner: Option<UserWithVotes>) -> Self {
+        HallOfFameEntry {
+            season: season.into(),
+            winner: winner.map(|user| Winner {
+                username: user.username,
+                votes: (!user.hide_count).then_some(user.votes),
+            }),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Serialize;
+
+use crate::models::user::UserWithVotes;
+
+/// How many days the vote series goes back at most
+const MAX_SERIES_DAYS: i64 = 365;
+
+#[derive(Serialize, Default)]
+pub struct UserProfileResponse {
+    pub username: String,
+    /// `None` when the owner hides the count
+    pub votes: Option<i64>,
+    /// Same rank as in the leaderboard, `None` without votes
+    pub rank: Option<i64>,
+    pub first_vote_at: Option<String>,
+    /// Votes per day (UTC) from the first vote of the season until today,
+    /// empty when the owner hides the count
+    pub daily_votes: Vec<DailyVotes>,
+}
+
+#[derive(Serialize, Default)]
+pub struct DailyVotes {
+    pub date: NaiveDate,
+    pub votes: i64,
+}
+
+impl UserProfileResponse {
+    pub fn new(
+        username: String,
+        hide_count: bool,
+        ranked: Option<UserWithVotes>,
+        first_vote_at: Option<DateTimeWithTimeZone>,
+        daily_votes: &[(NaiveDate, i64)],
+    ) -> Self {
+        let first_vote_at = first_vote_at.map(|time| time.to_rfc3339());
+
+        if hide_count {
+            return UserProfileResponse {
+                username,
+                votes: None,
+                rank: ranked.map(|user| user.rank),
+                first_vote_at,
+                daily_votes: Vec::new(),
+            };
+        }
+
+        UserProfileResponse {
+            username,
+            votes: Some(ranked.as_ref().map_or(0, |user| user.votes)),
+            rank: ranked.map(|user| user.rank),
+            first_vote_at,
+            daily_votes: fill_days(daily_votes),
+        }
+    }
+}
+
+/// Fills the days without votes, from the first day with votes until today
+fn fill_days(daily_votes: &[(NaiveDate, i64)]) -> Vec<DailyVotes> {
+    let Some((first, _)) = daily_votes.first() else {
+        return Vec::new();
+    };
+
+    let today = Utc::now().date_naive();
+    let start = (*first).max(today - Duration::days(MAX_SERIES_DAYS - 1));
+
+    let mut votes: BTreeMap<NaiveDate, i64> = start
+        .iter_days()
+        .take_while(|date| *date <= today)
+        .map(|date| (date, 0))
+        .collect();
+
+    for (date, count) in daily_votes {
+        if let Some(votes) = votes.get_mut(date) {
+            *votes = *count;
+        }
+    }
+
+    votes
+        .into_iter()
+        .map(|(date, votes)| DailyVotes { date, votes })
+        .collect()
+}
@@ -0,0 +1,88 @@
+use loco_rs::{prelude::*, worker};
+use sea_orm::{EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    leaderboard_cache::LeaderboardCache,
+    models::{
+        _entities::{crush_match, user, voter},
+        voter::VoteStatus,
+    },
+    verifiers::Verifiers,
+};
+
+/// Checks the username of a pending vote and confirms or rejects it
+pub struct VerifyVoteWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct VerifyVoteWorkerArgs {
+    pub voter_id: i32,
+}
+
+impl worker::AppWorker<VerifyVoteWorkerArgs> for VerifyVoteWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+}
+
+#[async_trait]
+impl worker::Worker<VerifyVoteWorkerArgs> for VerifyVoteWorker {
+    async fn perform(&self, args: VerifyVoteWorkerArgs) -> worker::Result<()> {
+        if let Err(err) = self.verify(args.voter_id).await {
+            error!("Could not verify vote {}: {}", args.voter_id, err);
+        }
+
+        Ok(())
+    }
+}
+
+impl VerifyVoteWorker {
+    async fn verify(&self, voter_id: i32) -> Result<()> {
+        let Some((voter, Some(voted_user))) = voter::Entity::find_by_id(voter_id)
+            .find_also_related(user::Entity)
+            .one(&self.ctx.db)
+            .await?
+        else {
+            // unvoted in the meantime
+            return Ok(());
+        };
+
+        if voter.status() != VoteStatus::Pending {
+            return Ok(());
+        }
+
+        let verifiers = Verifiers::shared(&self.ctx).await?;
+
+        let status = match verifiers.username.exists(&voted_user.username).await {
+            Ok(true) => VoteStatus::Confirmed,
+            Ok(false) => VoteStatus::NotFound,
+            Err(err) => {
+                error!("Threads not working: {}", err);
+                VoteStatus::Failed
+            }
+        };
+
+        // the username was never on threads, its vote and user are dropped so
+        // made up usernames don't pile up
+        if status == VoteStatus::NotFound {
+            let txn = self.ctx.db.begin().await?;
+            voter::Entity::delete_by_id(voter.id).exec(&txn).await?;
+            user::Model::delete_if_orphan(&txn, voted_user.id).await?;
+            txn.commit().await?;
+
+            return Ok(());
+        }
+
+        let voter = voter::Model::set_status(&self.ctx.db, voter.id, status).await?;
+        if status == VoteStatus::Confirmed {
+            LeaderboardCache::shared(&self.ctx).await?.invalidate();
+        }
+
+        crush_match::Model::detect(&self.ctx.db, &voter).await?;
+
+        Ok(())
+    }
+}
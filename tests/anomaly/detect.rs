@@ -0,0 +1,100 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use threads_crush::{
+    anomaly::{detect, Flag, VoteSample},
+    common::settings::AnomalySettings,
+    models::vote_flag::FlagKind,
+};
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 20, 12, 0, 0).unwrap()
+}
+
+/// A vote for user 1, `seconds` after the start
+fn vote(voter_id: i32, seconds: i64, network: &str) -> VoteSample {
+    VoteSample {
+        voter_id,
+        voted_user_id: 1,
+        network: Some(network.to_string()),
+        created_at: start() + Duration::seconds(seconds),
+    }
+}
+
+fn flagged(flags: &[Flag], kind: FlagKind) -> Vec<i32> {
+    let mut voters: Vec<i32> = flags
+        .iter()
+        .filter(|flag| flag.kind == kind)
+        .map(|flag| flag.voter_id)
+        .collect();
+    voters.sort_unstable();
+
+    voters
+}
+
+#[test]
+fn flags_network_clusters() {
+    let settings = AnomalySettings::default();
+    // hours apart, so only the network stands out
+    let mut votes: Vec<VoteSample> = (1..=5)
+        .map(|id| vote(id, i64::from(id) * 7_000, "10.0.0.0/24"))
+        .collect();
+    votes.push(vote(6, 50_000, "10.0.1.0/24"));
+
+    let flags = detect(&settings, &votes);
+    assert_eq!(
+        flagged(&flags, FlagKind::NetworkCluster),
+        vec![1, 2, 3, 4, 5]
+    );
+    assert!(flags
+        .iter()
+        .all(|flag| flag.kind == FlagKind::NetworkCluster));
+
+    votes.remove(0);
+    assert!(detect(&settings, &votes).is_empty());
+}
+
+#[test]
+fn flags_spikes() {
+    let settings = AnomalySettings::default();
+    // a vote every couple of hours, then a dozen in one hour
+    let mut votes: Vec<VoteSample> = (0..6)
+        .map(|i| vote(i + 1, i64::from(i) * 7_200, &format!("10.0.{}.0/24", i)))
+        .collect();
+    let gaps = [
+        0, 40, 400, 410, 900, 1_300, 1_320, 2_000, 2_600, 2_610, 3_000, 3_500,
+    ];
+    votes.extend(
+        gaps.iter()
+            .enumerate()
+            .map(|(i, gap)| vote(100 + i as i32, 50_400 + gap, &format!("10.1.{}.0/24", i))),
+    );
+
+    let flags = detect(&settings, &votes);
+    assert_eq!(
+        flagged(&flags, FlagKind::Spike),
+        (100..112).collect::<Vec<_>>()
+    );
+    assert!(flagged(&flags, FlagKind::NetworkCluster).is_empty());
+    assert!(flagged(&flags, FlagKind::EvenTiming).is_empty());
+}
+
+#[test]
+fn flags_even_timing() {
+    let settings = AnomalySettings::default();
+    let votes: Vec<VoteSample> = (0..6)
+        .map(|i| vote(i + 1, i64::from(i) * 60, &format!("10.0.{}.0/24", i)))
+        .collect();
+
+    let flags = detect(&settings, &votes);
+    assert_eq!(
+        flagged(&flags, FlagKind::EvenTiming),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+
+    // the same votes with human jitter
+    let jittered: Vec<VoteSample> = [0, 45, 130, 170, 260, 300]
+        .iter()
+        .enumerate()
+        .map(|(i, seconds)| vote(i as i32 + 1, *seconds, &format!("10.0.{}.0/24", i)))
+        .collect();
+    assert!(detect(&settings, &jittered).is_empty());
+}
@@ -0,0 +1,156 @@
+use std::{
+    fs::{self, File},
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use threads_crush::{
+    common::settings::{IpListPolicy, IpListSettings, IpReputationSettings},
+    ip_reputation::{parse_list, trie::PrefixTrie, IpReputation},
+};
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+fn settings(path: &str, policy: IpListPolicy) -> IpReputationSettings {
+    IpReputationSettings {
+        lists: vec![IpListSettings {
+            name: "hosting".to_string(),
+            path: path.to_string(),
+            policy,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn matches_prefixes() {
+    let trie: PrefixTrie = [
+        "10.0.0.0/8",
+        "192.168.1.0/24",
+        "2001:db8::/32",
+        "0.0.0.0/32",
+    ]
+    .iter()
+    .map(|network| network.parse().unwrap())
+    .collect();
+
+    assert!(trie.contains(ip("10.255.0.1")));
+    assert!(trie.contains(ip("192.168.1.200")));
+    assert!(!trie.contains(ip("192.168.2.1")));
+    assert!(trie.contains(ip("2001:db8:ffff::1")));
+    assert!(!trie.contains(ip("2001:db9::1")));
+    assert!(trie.contains(ip("0.0.0.0")));
+    assert!(!trie.contains(ip("0.0.0.1")));
+    // IPv4-mapped IPv6 addresses are looked up as IPv4
+    assert!(trie.contains(ip("::ffff:10.1.2.3")));
+}
+
+#[test]
+fn skips_covered_networks() {
+    let mut trie = PrefixTrie::new();
+    trie.insert("10.0.0.0/8".parse().unwrap());
+    trie.insert("10.1.0.0/16".parse().unwrap());
+    trie.insert("10.0.0.0/8".parse().unwrap());
+
+    assert_eq!(trie.len(), 1);
+    assert!(trie.contains(ip("10.1.2.3")));
+}
+
+#[test]
+fn drops_networks_a_shorter_one_covers() {
+    let mut trie = PrefixTrie::new();
+    trie.insert("10.1.0.0/16".parse().unwrap());
+    trie.insert("10.2.3.0/24".parse().unwrap());
+    assert_eq!(trie.len(), 2);
+
+    trie.insert("10.0.0.0/8".parse().unwrap());
+
+    // a root per tree and the 8 nodes down to 10.0.0.0/8
+    assert_eq!(trie.len(), 1);
+    assert_eq!(trie.node_count(), 2 + 8);
+
+    // the cut off nodes are reused
+    trie.insert("192.168.0.0/16".parse().unwrap());
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.node_count(), 2 + 8 + 16);
+}
+
+#[test]
+fn looks_up_mapped_networks_as_ipv4() {
+    let mut trie = PrefixTrie::new();
+    trie.insert("::ffff:10.0.0.0/104".parse().unwrap());
+
+    assert!(trie.contains(ip("10.1.2.3")));
+    assert!(trie.contains(ip("::ffff:10.1.2.3")));
+    assert!(!trie.contains(ip("11.0.0.1")));
+
+    // a network spanning every mapped address spans IPv4
+    let mut trie = PrefixTrie::new();
+    trie.insert("::/0".parse().unwrap());
+    assert!(trie.contains(ip("203.0.113.7")));
+}
+
+#[test]
+fn parses_list_files() {
+    let (networks, invalid) =
+        parse_list(&fs::read_to_string("tests/fixtures/ip_lists/hosting.txt").unwrap());
+
+    assert_eq!(networks.len(), 3);
+    assert_eq!(invalid, 1);
+    assert!(networks.contains(ip("203.0.113.9")));
+    assert!(networks.contains(ip("198.51.100.7")));
+    assert!(!networks.contains(ip("198.51.100.8")));
+    assert!(networks.contains(ip("2001:db8:1234::1")));
+}
+
+#[test]
+fn looks_up_lists() {
+    let reputation = IpReputation::from_settings(&settings(
+        "tests/fixtures/ip_lists/hosting.txt",
+        IpListPolicy::Challenge,
+    ))
+    .unwrap();
+
+    let matches = reputation.lookup(ip("203.0.113.9"));
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].name, "hosting");
+    assert_eq!(matches[0].policy, IpListPolicy::Challenge);
+    assert!(reputation.lookup(ip("192.0.2.1")).is_empty());
+
+    assert!(IpReputation::from_settings(&settings(
+        "tests/fixtures/ip_lists/missing.txt",
+        IpListPolicy::Block
+    ))
+    .is_err());
+}
+
+#[test]
+fn reloads_changed_files() {
+    let path = std::env::temp_dir().join(format!("ip_list_{}.txt", std::process::id()));
+    fs::write(&path, "192.0.2.0/24\n").unwrap();
+    let reputation =
+        IpReputation::from_settings(&settings(path.to_str().unwrap(), IpListPolicy::Flag)).unwrap();
+
+    assert_eq!(reputation.reload(), 0);
+    assert_eq!(reputation.lookup(ip("192.0.2.1")).len(), 1);
+
+    fs::write(&path, "198.51.100.0/24\n").unwrap();
+    // the write may land in the same tick of a coarse clock
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+
+    assert_eq!(reputation.reload(), 1);
+    assert!(reputation.lookup(ip("192.0.2.1")).is_empty());
+    assert_eq!(reputation.lookup(ip("198.51.100.1")).len(), 1);
+
+    // a list that can't be read keeps its networks
+    fs::remove_file(&path).unwrap();
+    assert_eq!(reputation.reload(), 0);
+    assert_eq!(reputation.lookup(ip("198.51.100.1")).len(), 1);
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use loco_rs::{controller::ErrorDetail, Error};
+use threads_crush::{
+    common::settings::LeaderboardCacheSettings,
+    controllers::leaderboard::{respond, STALE_HEADER},
+    leaderboard_cache::{LeaderboardCache, LeaderboardKey},
+    models::user::LeaderboardWindow,
+    views::leaderboard::LeaderboardResponse,
+};
+
+fn key(page: u64) -> LeaderboardKey {
+    LeaderboardKey {
+        season: None,
+        username: None,
+        window: LeaderboardWindow::All,
+        page,
+    }
+}
+
+#[test]
+fn serves_cached_pages() {
+    let cache = LeaderboardCache::new(&LeaderboardCacheSettings::default());
+    let response = Arc::new(LeaderboardResponse::default());
+
+    assert!(cache.get(&key(1)).is_none());
+    cache.insert(key(1), cache.generation(), response.clone());
+
+    assert!(Arc::ptr_eq(&cache.get(&key(1)).unwrap(), &response));
+    assert!(cache.get(&key(2)).is_none());
+}
+
+#[test]
+fn invalidates_on_writes() {
+    let cache = LeaderboardCache::new(&LeaderboardCacheSettings::default());
+
+    cache.insert(
+        key(1),
+        cache.generation(),
+        Arc::new(LeaderboardResponse::default()),
+    );
+    cache.invalidate();
+    assert!(cache.get(&key(1)).is_none());
+    // still there for when the database is down
+    assert!(cache.get_stale(&key(1)).is_some());
+
+    // read before the write, so it is outdated as soon as it is stored
+    let generation = cache.generation();
+    cache.invalidate();
+    cache.insert(key(2), generation, Arc::new(LeaderboardResponse::default()));
+    assert!(cache.get(&key(2)).is_none());
+}
+
+#[test]
+fn bounds_staleness() {
+    let cache = LeaderboardCache::new(&LeaderboardCacheSettings {
+        ttl: 0,
+        stale_ttl: 0,
+        ..Default::default()
+    });
+
+    cache.insert(
+        key(1),
+        cache.generation(),
+        Arc::new(LeaderboardResponse::default()),
+    );
+
+    assert!(cache.get(&key(1)).is_none());
+    assert!(cache.get_stale(&key(1)).is_none());
+}
+
+#[test]
+fn bounds_entries() {
+    let cache = LeaderboardCache::new(&LeaderboardCacheSettings {
+        max_entries: 2,
+        ..Default::default()
+    });
+
+    for page in 1..=3 {
+        cache.insert(
+            key(page),
+            cache.generation(),
+            Arc::new(LeaderboardResponse::default()),
+        );
+    }
+
+    assert!(cache.get(&key(3)).is_some());
+    // only the oldest page made room
+    assert!(cache.get(&key(2)).is_some());
+    assert!(cache.get(&key(1)).is_none());
+}
+
+#[test]
+fn serves_stale_pages_when_reading_fails() {
+    let cache = LeaderboardCache::new(&LeaderboardCacheSettings::default());
+
+    // nothing cached yet, the error goes through
+    assert!(respond(
+        &cache,
+        key(1),
+        cache.generation(),
+        Err(Error::Message("database is down".to_string())),
+    )
+    .is_err());
+
+    let response = respond(
+        &cache,
+        key(1),
+        cache.generation(),
+        Ok(LeaderboardResponse::default()),
+    )
+    .unwrap();
+    assert!(response.headers().get(STALE_HEADER).is_none());
+
+    cache.invalidate();
+    let response = respond(
+        &cache,
+        key(1),
+        cache.generation(),
+        Err(Error::Message("database is down".to_string())),
+    )
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(STALE_HEADER).unwrap(), "0");
+
+    // missing pages aren't served stale
+    assert!(respond(
+        &cache,
+        key(1),
+        cache.generation(),
+        Err(Error::CustomError(
+            StatusCode::NOT_FOUND,
+            ErrorDetail::new("PAGE_NOT_FOUND", "Page does not exist"),
+        )),
+    )
+    .is_err());
+}
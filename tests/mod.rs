@@ -0,0 +1,10 @@
+mod anomaly;
+mod ip_reputation;
+mod leaderboard_cache;
+mod models;
+mod rate_limit;
+mod risk;
+mod tasks;
+mod utils;
+mod verifiers;
+mod workers;
@@ -0,0 +1,68 @@
+use loco_rs::{model::ModelError, testing};
+use serial_test::serial;
+use threads_crush::{app::App, models::_entities::blocked_username};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_block_username() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    blocked_username::Model::block(&boot.app_context.db, "spammer", None)
+        .await
+        .unwrap();
+    let blocked =
+        blocked_username::Model::block(&boot.app_context.db, "spammer", Some("spam".to_string()))
+            .await
+            .unwrap();
+
+    assert_eq!(blocked.reason.as_deref(), Some("spam"));
+    assert_eq!(
+        blocked_username::Model::list(&boot.app_context.db)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(
+        blocked_username::Model::is_blocked(&boot.app_context.db, "spammer")
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_unblock_username() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    blocked_username::Model::block(&boot.app_context.db, "spammer", None)
+        .await
+        .unwrap();
+    blocked_username::Model::unblock(&boot.app_context.db, "spammer")
+        .await
+        .unwrap();
+
+    assert!(
+        !blocked_username::Model::is_blocked(&boot.app_context.db, "spammer")
+            .await
+            .unwrap()
+    );
+    assert!(matches!(
+        blocked_username::Model::unblock(&boot.app_context.db, "spammer").await,
+        Err(ModelError::EntityNotFound)
+    ));
+}
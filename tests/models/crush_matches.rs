@@ -0,0 +1,172 @@
+use chrono::Utc;
+use loco_rs::testing;
+use sea_orm::{DatabaseConnection, EntityTrait, JoinType, QuerySelect, RelationTrait};
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{crush_match, season, user, voter, voter_identity},
+        voter::{Screened, VoteStatus, VoterKey},
+    },
+    utils::address_hash::AddressHasher,
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Casts a confirmed vote for `crush` as the verified voter `username`
+async fn verified_vote(
+    boot: &loco_rs::boot::BootResult,
+    username: &str,
+    crush: &str,
+    ip: &str,
+) -> voter_identity::Model {
+    let db: &DatabaseConnection = &boot.app_context.db;
+    let (identity, _) = voter_identity::Model::claim(db, username).await.unwrap();
+    let identity = identity.confirm(db).await.unwrap();
+
+    vote_as(boot, &identity, crush, ip).await;
+
+    identity
+}
+
+async fn vote_as(
+    boot: &loco_rs::boot::BootResult,
+    identity: &voter_identity::Model,
+    crush: &str,
+    ip: &str,
+) {
+    let db: &DatabaseConnection = &boot.app_context.db;
+    let season = season::Model::find_active(db).await.unwrap().unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash(ip);
+    let crush = user::Model::add(db, crush).await.unwrap();
+
+    let voter = voter::Model::add(
+        db,
+        VoterKey::Identity(identity.id, None),
+        season.id,
+        &address,
+        None,
+        crush.id,
+        VoteStatus::Confirmed,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+    crush_match::Model::detect(db, &voter).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn can_match_both_ways() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let alice = verified_vote(&boot, "alice", "bob", "192.0.2.1").await;
+    let bob = verified_vote(&boot, "bob", "alice", "192.0.2.2").await;
+    let season_id = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .id;
+
+    let matches = crush_match::Model::find_for_identity(&boot.app_context.db, alice.id, season_id)
+        .await
+        .unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].1.id, bob.id);
+
+    // identity a is the lower id
+    let first: Option<String> = crush_match::Entity::find()
+        .select_only()
+        .join(
+            JoinType::InnerJoin,
+            crush_match::Relation::VoterIdentity1.def(),
+        )
+        .column(voter_identity::Column::Username)
+        .into_tuple()
+        .one(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(first.as_deref(), Some("alice"));
+}
+
+#[tokio::test]
+#[serial]
+async fn banning_deletes_matches_of_the_banned_account() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let alice = verified_vote(&boot, "alice", "bob", "192.0.2.1").await;
+    let bob = verified_vote(&boot, "bob", "alice", "192.0.2.2").await;
+
+    let season_id = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .id;
+
+    user::Model::delete_with_votes(&boot.app_context.db, "bob")
+        .await
+        .unwrap();
+
+    for identity in [alice, bob] {
+        assert!(crush_match::Model::find_for_identity(
+            &boot.app_context.db,
+            identity.id,
+            season_id
+        )
+        .await
+        .unwrap()
+        .is_empty());
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn can_match_again_next_season() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let alice = verified_vote(&boot, "alice", "bob", "192.0.2.1").await;
+    let bob = verified_vote(&boot, "bob", "alice", "192.0.2.2").await;
+    let first = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let next = season::Model::start(&boot.app_context.db, "Season 2", Utc::now().into())
+        .await
+        .unwrap();
+    assert!(
+        crush_match::Model::find_for_identity(&boot.app_context.db, alice.id, next.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    vote_as(&boot, &alice, "bob", "192.0.2.1").await;
+    vote_as(&boot, &bob, "alice", "192.0.2.2").await;
+
+    for season_id in [first.id, next.id] {
+        let matches =
+            crush_match::Model::find_for_identity(&boot.app_context.db, alice.id, season_id)
+                .await
+                .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].1.id, bob.id);
+    }
+}
@@ -0,0 +1,9 @@
+mod blocked_usernames;
+mod crush_matches;
+mod rate_limit_buckets;
+mod reports;
+mod seasons;
+mod users;
+mod vote_flags;
+mod voter_identities;
+mod voters;
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use loco_rs::testing;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    common::settings::RateLimitRule,
+    models::_entities::rate_limit_bucket,
+    rate_limit::{postgres::PostgresRateLimitStore, RateLimitStore},
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn rule() -> RateLimitRule {
+    RateLimitRule {
+        path: "/api/vote".to_string(),
+        method: Some("POST".to_string()),
+        burst: 2,
+        per_minute: 1,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn can_limit_after_burst() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let store = PostgresRateLimitStore::new(boot.app_context.db.clone(), Duration::from_secs(120));
+    let rule = rule();
+
+    assert!(store.take("a", &rule).await.unwrap().is_none());
+    assert!(store.take("a", &rule).await.unwrap().is_none());
+
+    let wait = store.take("a", &rule).await.unwrap().unwrap();
+    assert!(wait.as_secs() > 0 && wait.as_secs() <= 60);
+
+    // other clients have their own bucket
+    assert!(store.take("b", &rule).await.unwrap().is_none());
+
+    // instances sharing the table share the buckets
+    let other = PostgresRateLimitStore::new(boot.app_context.db.clone(), Duration::from_secs(120));
+    assert!(other.take("a", &rule).await.unwrap().is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn can_prune_full_buckets() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let rule = rule();
+
+    let store = PostgresRateLimitStore::new(boot.app_context.db.clone(), Duration::from_secs(120));
+    store.take("a", &rule).await.unwrap();
+    assert_eq!(store.prune().await.unwrap(), 0);
+
+    let store = PostgresRateLimitStore::new(boot.app_context.db.clone(), Duration::ZERO);
+    assert_eq!(store.prune().await.unwrap(), 1);
+    assert_eq!(
+        rate_limit_bucket::Entity::find()
+            .count(&boot.app_context.db)
+            .await
+            .unwrap(),
+        0
+    );
+}
@@ -0,0 +1,104 @@
+use loco_rs::testing;
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{report, user},
+        report::{FileReportError, ReportAction, ReportStatus, ResolveReportError},
+        user::LeaderboardWindow,
+    },
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_file_report_once() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+
+    let first = report::Model::file(&boot.app_context.db, zuck.id, "harassment", "reporter")
+        .await
+        .unwrap();
+    let again = report::Model::file(&boot.app_context.db, zuck.id, "spam", "reporter").await;
+    let other = report::Model::file(&boot.app_context.db, zuck.id, "spam", "someone else")
+        .await
+        .unwrap();
+
+    assert!(matches!(again, Err(FileReportError::AlreadyReported)));
+    assert_ne!(first.id, other.id);
+    assert_eq!(first.status(), ReportStatus::Open);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_suspend_from_report() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+    let first = report::Model::file(&boot.app_context.db, zuck.id, "harassment", "reporter")
+        .await
+        .unwrap();
+    let other = report::Model::file(&boot.app_context.db, zuck.id, "spam", "someone else")
+        .await
+        .unwrap();
+
+    let resolved = report::Model::resolve(
+        &boot.app_context.db,
+        first.id,
+        ReportAction::Suspend,
+        Some("harassing".to_string()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(resolved.status(), ReportStatus::Resolved);
+    assert!(matches!(
+        report::Model::resolve(&boot.app_context.db, other.id, ReportAction::Dismiss, None).await,
+        Err(ResolveReportError::AlreadyClosed)
+    ));
+    assert!(
+        user::Model::find_by_username(&boot.app_context.db, "zuck")
+            .await
+            .unwrap()
+            .suspended
+    );
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert!(users.iter().all(|user| user.username != "zuck"));
+
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        None,
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 2);
+}
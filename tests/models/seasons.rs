@@ -0,0 +1,118 @@
+use chrono::Utc;
+use loco_rs::testing;
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{season, user, voter},
+        user::LeaderboardWindow,
+        voter::{Screened, VoteStatus, VoterKey},
+    },
+    utils::address_hash::AddressHasher,
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_start_season_archiving_the_previous_one() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let previous = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let season = season::Model::start(&boot.app_context.db, "Season 2", Utc::now().into())
+        .await
+        .unwrap();
+
+    let active = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(active.id, season.id);
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        Some(season.id),
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert!(users.is_empty());
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        Some(season.id),
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!((pagination.entries, pagination.last), (0, 0));
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        Some(previous.id),
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(users.len(), 3);
+
+    let hall_of_fame = season::Model::hall_of_fame(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(hall_of_fame.len(), 1);
+    assert_eq!(hall_of_fame[0].0.id, previous.id);
+    assert_eq!(hall_of_fame[0].1.as_ref().unwrap().username, "zuck");
+}
+
+#[tokio::test]
+#[serial]
+async fn can_vote_again_in_a_new_season() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let season = season::Model::start(&boot.app_context.db, "Season 2", Utc::now().into())
+        .await
+        .unwrap();
+    let mosseri = user::Model::find_by_username(&boot.app_context.db, "mosseri")
+        .await
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+
+    let voter = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        mosseri.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(voter.season_id, Some(season.id));
+}
@@ -1,6 +1,14 @@
-use threads_crush::app::App;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use loco_rs::testing;
+use sea_orm::{sea_query::Expr, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
 use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{season, user, voter, voter_identity},
+        user::{LeaderboardWindow, ProfileSettings},
+    },
+};
 
 macro_rules! configure_insta {
     ($($expr:expr),*) => {
@@ -12,20 +20,353 @@ macro_rules! configure_insta {
 
 #[tokio::test]
 #[serial]
-async fn test_model() {
+async fn can_find_leaderboard() {
     configure_insta!();
 
     let boot = testing::boot_test::<App>().await.unwrap();
     testing::seed::<App>(&boot.app_context.db).await.unwrap();
 
-    // query your model, e.g.:
-    //
-    // let item = models::posts::Model::find_by_pid(
-    //     &boot.app_context.db,
-    //     "11111111-1111-1111-1111-111111111111",
-    // )
-    // .await;
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    let ranking: Vec<(String, i64, i64)> = users
+        .into_iter()
+        .map(|user| (user.username, user.votes, user.rank))
+        .collect();
 
-    // snapshot the result:
-    // assert_debug_snapshot!(item);
+    assert_eq!(
+        ranking,
+        vec![
+            ("zuck".to_string(), 3, 1),
+            ("mosseri".to_string(), 2, 2),
+            ("threadscrush".to_string(), 1, 3),
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_filter_leaderboard_keeping_rank() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let username = Some("mos".to_string());
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &username,
+        None,
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].username, "mosseri");
+    assert_eq!(users[0].rank, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_paginate_leaderboard() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        2,
+        &None,
+        None,
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 3);
+    assert_eq!(pagination.last, 2);
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        2,
+        2,
+    )
+    .await
+    .unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].username, "threadscrush");
+
+    // page 0 reads like page 1 instead of overflowing the offset
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        0,
+        2,
+    )
+    .await
+    .unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].username, "zuck");
+}
+
+#[tokio::test]
+#[serial]
+async fn can_rank_leaderboard_within_window() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+    voter::Entity::update_many()
+        .col_expr(
+            voter::Column::CreatedAt,
+            Expr::value(Utc::now() - Duration::days(3)),
+        )
+        .filter(voter::Column::VotedUserId.eq(zuck.id))
+        .exec(&boot.app_context.db)
+        .await
+        .unwrap();
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::Day,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    let ranking: Vec<(String, i64)> = users
+        .into_iter()
+        .map(|user| (user.username, user.rank))
+        .collect();
+    assert_eq!(
+        ranking,
+        vec![("mosseri".to_string(), 1), ("threadscrush".to_string(), 2)]
+    );
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        None,
+        LeaderboardWindow::Day,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 2);
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::Week,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(users.len(), 3);
+    assert_eq!(users[0].username, "zuck");
+}
+
+#[tokio::test]
+#[serial]
+async fn can_search_users() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let (users, pagination) =
+        user::Model::search(&boot.app_context.db, &Some("ZU".to_string()), 1, 10)
+            .await
+            .unwrap();
+
+    assert_eq!(pagination.entries, 1);
+    assert_eq!(users[0].username, "zuck");
+    assert_eq!(users[0].votes, 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_respect_profile_settings() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap()
+        .update_settings(
+            &boot.app_context.db,
+            ProfileSettings {
+                hidden: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    user::Model::find_by_username(&boot.app_context.db, "mosseri")
+        .await
+        .unwrap()
+        .update_settings(
+            &boot.app_context.db,
+            ProfileSettings {
+                hide_count: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    let ranking: Vec<(String, i64, bool)> = users
+        .into_iter()
+        .map(|user| (user.username, user.rank, user.hide_count))
+        .collect();
+
+    assert_eq!(
+        ranking,
+        vec![
+            ("mosseri".to_string(), 1, true),
+            ("threadscrush".to_string(), 2, false),
+        ]
+    );
+
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        None,
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_claim_profile() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let (identity, _) = voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+        .await
+        .unwrap();
+    let identity = identity.confirm(&boot.app_context.db).await.unwrap();
+
+    let user = user::Model::claim(&boot.app_context.db, "newcomer", identity.id)
+        .await
+        .unwrap();
+
+    assert_eq!(user.owner_identity_id, Some(identity.id));
+    assert!(user.claimed_at.is_some());
+    assert_eq!(
+        user::Model::find_by_owner(&boot.app_context.db, identity.id)
+            .await
+            .unwrap()
+            .id,
+        user.id
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_count_daily_votes_within_season() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+    let votes = voter::Entity::find()
+        .filter(voter::Column::VotedUserId.eq(zuck.id))
+        .order_by_asc(voter::Column::Id)
+        .all(&boot.app_context.db)
+        .await
+        .unwrap();
+    // the second vote is still on May 1st in UTC
+    for (voter, created_at) in votes.iter().zip([
+        "2024-05-01T10:00:00Z",
+        "2024-05-02T01:30:00+02:00",
+        "2024-05-03T12:00:00Z",
+    ]) {
+        voter::Entity::update_many()
+            .col_expr(
+                voter::Column::CreatedAt,
+                Expr::value(DateTime::parse_from_rfc3339(created_at).unwrap()),
+            )
+            .filter(voter::Column::Id.eq(voter.id))
+            .exec(&boot.app_context.db)
+            .await
+            .unwrap();
+    }
+    let season_id = votes[0].season_id;
+
+    let daily_votes = zuck
+        .find_daily_votes(&boot.app_context.db, season_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        daily_votes,
+        vec![
+            (NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(), 2),
+            (NaiveDate::from_ymd_opt(2024, 5, 3).unwrap(), 1),
+        ]
+    );
+    assert_eq!(
+        zuck.find_first_vote_at(&boot.app_context.db, season_id)
+            .await
+            .unwrap(),
+        Some(DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap())
+    );
+
+    let next = season::Model::start(&boot.app_context.db, "Season 2", Utc::now().into())
+        .await
+        .unwrap();
+    assert!(zuck
+        .find_daily_votes(&boot.app_context.db, Some(next.id))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        zuck.find_first_vote_at(&boot.app_context.db, Some(next.id))
+            .await
+            .unwrap(),
+        None
+    );
 }
@@ -0,0 +1,145 @@
+use loco_rs::testing;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serial_test::serial;
+use threads_crush::{
+    anomaly::Flag,
+    app::App,
+    models::{
+        _entities::{user, vote_flag, voter},
+        user::LeaderboardWindow,
+        vote_flag::FlagKind,
+    },
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_flag_and_dismiss_votes() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+    let voter = voter::Entity::find()
+        .filter(voter::Column::VotedUserId.eq(zuck.id))
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let votes = || async {
+        user::Model::find_ranked(&boot.app_context.db, "zuck", None)
+            .await
+            .unwrap()
+            .map_or(0, |user| user.votes)
+    };
+    assert_eq!(votes().await, 3);
+
+    let flags = [
+        Flag {
+            voter_id: voter.id,
+            kind: FlagKind::Spike,
+            detail: "spike".to_string(),
+        },
+        Flag {
+            voter_id: voter.id,
+            kind: FlagKind::EvenTiming,
+            detail: "even".to_string(),
+        },
+    ];
+    assert_eq!(
+        vote_flag::Model::record(&boot.app_context.db, &flags)
+            .await
+            .unwrap(),
+        2
+    );
+    // flagging again is a no-op
+    assert_eq!(
+        vote_flag::Model::record(&boot.app_context.db, &flags)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let flagged = vote_flag::Model::flagged_users(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(flagged[0].username, "zuck");
+    assert_eq!(flagged[0].flagged_votes, 1);
+    assert_eq!(
+        flagged[0].kinds,
+        vec![FlagKind::Spike, FlagKind::EvenTiming]
+    );
+
+    assert_eq!(votes().await, 2);
+
+    assert_eq!(
+        vote_flag::Model::dismiss_for_user(&boot.app_context.db, zuck.id)
+            .await
+            .unwrap(),
+        2
+    );
+    assert!(vote_flag::Model::flagged_users(&boot.app_context.db)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(votes().await, 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn flagged_only_users_are_not_counted() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let user = user::Model::find_by_username(&boot.app_context.db, "threadscrush")
+        .await
+        .unwrap();
+    let voter = voter::Entity::find()
+        .filter(voter::Column::VotedUserId.eq(user.id))
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    vote_flag::Model::record(
+        &boot.app_context.db,
+        &[Flag {
+            voter_id: voter.id,
+            kind: FlagKind::Spike,
+            detail: "spike".to_string(),
+        }],
+    )
+    .await
+    .unwrap();
+
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        None,
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 2);
+    assert!(user
+        .find_first_vote_at(&boot.app_context.db, None)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(user
+        .find_daily_votes(&boot.app_context.db, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,147 @@
+use chrono::{Duration, Utc};
+use loco_rs::testing;
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{season, user, voter, voter_identity},
+        voter::{Screened, VoteStatus, VoterError, VoterKey},
+        voter_identity::ClaimError,
+    },
+    utils::address_hash::AddressHasher,
+};
+
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_reclaim_while_challenge_is_open() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let (identity, token) = voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+        .await
+        .unwrap();
+    assert!(matches!(
+        voter_identity::Model::claim(&boot.app_context.db, "newcomer").await,
+        Err(ClaimError::Pending)
+    ));
+
+    // once the challenge expires, its token stops working and the username
+    // can be claimed again
+    let mut expired = identity.into_active_model();
+    expired.claimed_at = ActiveValue::set(Some((Utc::now() - Duration::hours(1)).into()));
+    expired.update(&boot.app_context.db).await.unwrap();
+
+    assert!(
+        voter_identity::Model::find_by_pending_token(&boot.app_context.db, &token)
+            .await
+            .is_err()
+    );
+    assert!(
+        voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_reclaim_after_verifying() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let (_, token) = voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+        .await
+        .unwrap();
+    let identity = voter_identity::Model::find_by_pending_token(&boot.app_context.db, &token)
+        .await
+        .unwrap()
+        .confirm(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert!(identity.is_verified());
+    assert_eq!(identity.claimed_at, None);
+
+    assert!(
+        voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_vote_anonymously_and_verified() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+    let (identity, _) = voter_identity::Model::claim(&boot.app_context.db, "newcomer")
+        .await
+        .unwrap();
+    let identity = identity.confirm(&boot.app_context.db).await.unwrap();
+
+    // 10.0.0.1 already voted anonymously in the seed
+    let voted = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+    let result = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Identity(identity.id, Some(&voted.hash)),
+        season.id,
+        &voted,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(VoterError::AlreadyVoted)));
+
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("192.0.2.1");
+    voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Identity(identity.id, Some(&address.hash)),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+    let result = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(VoterError::AlreadyVoted)));
+}
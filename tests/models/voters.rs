@@ -1,6 +1,17 @@
-use threads_crush::app::App;
+use chrono::{Duration, Utc};
 use loco_rs::testing;
+use sea_orm::{ColumnTrait, Database, EntityTrait, QueryFilter};
 use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{season, user, vote_flag, voter},
+        user::LeaderboardWindow,
+        vote_flag::FlagKind,
+        voter::{ChangeVoteError, DeleteVoterError, Screened, VoteStatus, VoterError, VoterKey},
+    },
+    utils::address_hash::{AddressHasher, VoterAddress},
+};
 
 macro_rules! configure_insta {
     ($($expr:expr),*) => {
@@ -12,20 +23,524 @@ macro_rules! configure_insta {
 
 #[tokio::test]
 #[serial]
-async fn test_model() {
+async fn can_find_by_address() {
     configure_insta!();
 
     let boot = testing::boot_test::<App>().await.unwrap();
     testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
 
-    // query your model, e.g.:
-    //
-    // let item = models::posts::Model::find_by_pid(
-    //     &boot.app_context.db,
-    //     "11111111-1111-1111-1111-111111111111",
-    // )
-    // .await;
+    let zuck = user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .unwrap();
+    let voter = voter::Model::find_by_address(&boot.app_context.db, &address.hash, season.id)
+        .await
+        .unwrap();
 
-    // snapshot the result:
-    // assert_debug_snapshot!(item);
+    assert_eq!(voter.voted_user_id, zuck.id);
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_vote_twice() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+    let result = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await;
+
+    assert!(matches!(result, Err(VoterError::AlreadyVoted)));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_store_screening_with_vote() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let hasher = AddressHasher::from_context(&boot.app_context).unwrap();
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+    let screened = || Screened {
+        shadow: true,
+        flag: Some((FlagKind::IpList, "listed in proxies".to_string())),
+    };
+
+    let address = hasher.hash("198.51.100.7");
+    let voter = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        screened(),
+    )
+    .await
+    .unwrap();
+    assert!(voter.shadow);
+
+    // nothing is stored for a vote that isn't added
+    let address = hasher.hash("10.0.0.1");
+    let result = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        screened(),
+    )
+    .await;
+    assert!(matches!(result, Err(VoterError::AlreadyVoted)));
+
+    let flags = vote_flag::Entity::find()
+        .all(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(
+        flags
+            .iter()
+            .map(|flag| (flag.voter_id, flag.kind()))
+            .collect::<Vec<_>>(),
+        vec![(voter.id, Some(FlagKind::IpList))]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[serial]
+async fn cannot_go_over_cap_concurrently() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("198.51.100.9");
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+
+    // the test pool has a single connection, which would run them one by one
+    let db = Database::connect(&boot.app_context.config.database.uri)
+        .await
+        .unwrap();
+
+    let votes = (0..5).map(|i| {
+        let db = db.clone();
+        let address = address.clone();
+
+        tokio::spawn(async move {
+            voter::Model::add(
+                &db,
+                VoterKey::Address(&format!("cookie {}", i)),
+                season.id,
+                &address,
+                Some(2),
+                nobody.id,
+                VoteStatus::Pending,
+                Screened::default(),
+            )
+            .await
+        })
+    });
+    let mut added = 0;
+    let mut capped = 0;
+    for vote in votes.collect::<Vec<_>>() {
+        match vote.await.unwrap() {
+            Ok(_) => added += 1,
+            Err(VoterError::AddressCapReached) => capped += 1,
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+
+    assert_eq!((added, capped), (2, 3));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_delete() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+
+    voter::Model::delete(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+    )
+    .await
+    .unwrap();
+
+    assert!(
+        voter::Model::find_by_address(&boot.app_context.db, &address.hash, season.id)
+            .await
+            .is_err()
+    );
+    assert!(matches!(
+        voter::Model::delete(
+            &boot.app_context.db,
+            VoterKey::Address(&address.hash),
+            season.id
+        )
+        .await,
+        Err(DeleteVoterError::NotFound)
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_change_vote() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+
+    let mosseri = user::Model::find_by_username(&boot.app_context.db, "mosseri")
+        .await
+        .unwrap();
+    let (previous, voter) = voter::Model::change(
+        &boot.app_context.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        mosseri.id,
+        Screened {
+            shadow: true,
+            flag: Some((FlagKind::IpList, "listed in proxies".to_string())),
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(previous.unwrap().username, "zuck");
+    assert_eq!(voter.voted_user_id, mosseri.id);
+    assert_eq!(voter.status(), VoteStatus::Confirmed);
+    assert!(voter.shadow);
+    let flags = vote_flag::Entity::find()
+        .filter(vote_flag::Column::VoterId.eq(voter.id))
+        .all(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(flags.len(), 1);
+    assert_eq!(flags[0].kind(), Some(FlagKind::IpList));
+
+    assert!(matches!(
+        voter::Model::change(
+            &boot.app_context.db,
+            VoterKey::Address("10.9.9.9"),
+            season.id,
+            mosseri.id,
+            Screened::default()
+        )
+        .await,
+        Err(ChangeVoteError::NotFound)
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_rekey_rotated_address() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let previous = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+    let address = VoterAddress {
+        hash: "rotated".to_string(),
+        previous_hash: Some(previous.hash.clone()),
+        network: Some("rotated-network".to_string()),
+        previous_network: previous.network.clone(),
+    };
+
+    let updated = voter::Model::rekey(&boot.app_context.db, &address, &address)
+        .await
+        .unwrap();
+
+    // the key and the address of the vote, and the network of the three
+    // votes from 10.0.0.0/24
+    assert_eq!(updated, 5);
+    assert!(
+        voter::Model::find_by_address(&boot.app_context.db, "rotated", season.id)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn can_invalidate_window() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let now = Utc::now();
+
+    let invalidated = voter::Model::invalidate(
+        &boot.app_context.db,
+        (now - Duration::hours(1)).into(),
+        (now + Duration::hours(1)).into(),
+        Some(
+            &AddressHasher::from_context(&boot.app_context)
+                .unwrap()
+                .stored_networks("10.0.0.0/24".parse().unwrap())
+                .unwrap(),
+        ),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(invalidated, 3);
+
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.0.1");
+    let voter = voter::Model::find_by_address(&boot.app_context.db, &address.hash, season.id)
+        .await
+        .unwrap();
+    assert_eq!(voter.status(), VoteStatus::Invalidated);
+    assert!(matches!(
+        voter::Model::change(
+            &boot.app_context.db,
+            VoterKey::Address(&address.hash),
+            season.id,
+            voter.voted_user_id,
+            Screened::default()
+        )
+        .await,
+        Err(ChangeVoteError::Invalidated)
+    ));
+    // unvoting would let the voter vote again
+    assert!(matches!(
+        voter::Model::delete(
+            &boot.app_context.db,
+            VoterKey::Address(&address.hash),
+            season.id
+        )
+        .await,
+        Err(DeleteVoterError::Invalidated)
+    ));
+}
+
+#[tokio::test]
+#[serial]
+async fn can_shadow_votes() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    for voter in voter::Entity::find()
+        .all(&boot.app_context.db)
+        .await
+        .unwrap()
+    {
+        voter::Model::set_status(&boot.app_context.db, voter.id, VoteStatus::Confirmed)
+            .await
+            .unwrap();
+    }
+
+    let shadowed = voter::Model::set_shadow_in_network(
+        &boot.app_context.db,
+        &AddressHasher::from_context(&boot.app_context)
+            .unwrap()
+            .stored_networks("10.0.1.0/24".parse().unwrap())
+            .unwrap(),
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(shadowed, 2);
+
+    let users = user::Model::find_leaderboard(
+        &boot.app_context.db,
+        &None,
+        None,
+        LeaderboardWindow::All,
+        1,
+        10,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        users
+            .iter()
+            .map(|user| user.username.as_str())
+            .collect::<Vec<_>>(),
+        vec!["zuck", "threadscrush"]
+    );
+    let pagination = user::Model::get_leaderboard_pagination(
+        &boot.app_context.db,
+        10,
+        &None,
+        None,
+        LeaderboardWindow::All,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pagination.entries, 2);
+    let mosseri = user::Model::find_by_username(&boot.app_context.db, "mosseri")
+        .await
+        .unwrap();
+    assert!(mosseri
+        .find_first_vote_at(&boot.app_context.db, None)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(mosseri
+        .find_daily_votes(&boot.app_context.db, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // a new voter cookie on the same address is still shadowed
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("10.0.1.1");
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+    let voter = voter::Model::add(
+        &boot.app_context.db,
+        VoterKey::Address("new cookie"),
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+    assert!(voter.shadow);
+}
+
+#[tokio::test]
+#[serial]
+async fn stays_shadowed_after_unvoting() {
+    configure_insta!();
+
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let season = season::Model::find_active(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let address = AddressHasher::from_context(&boot.app_context)
+        .unwrap()
+        .hash("192.0.2.1");
+    let nobody = user::Model::find_by_username(&boot.app_context.db, "nobody")
+        .await
+        .unwrap();
+    let key = VoterKey::Address(&address.hash);
+
+    voter::Model::add(
+        &boot.app_context.db,
+        key,
+        season.id,
+        &address,
+        None,
+        nobody.id,
+        VoteStatus::Confirmed,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+    voter::Model::set_shadow_by_ip_hashes(&boot.app_context.db, &[address.hash.as_str()], true)
+        .await
+        .unwrap();
+
+    voter::Model::delete(&boot.app_context.db, key, season.id)
+        .await
+        .unwrap();
+    assert!(
+        voter::Model::find_with_user(&boot.app_context.db, key, season.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(matches!(
+        voter::Model::delete(&boot.app_context.db, key, season.id).await,
+        Err(DeleteVoterError::NotFound)
+    ));
+
+    let voter = voter::Model::add(
+        &boot.app_context.db,
+        key,
+        season.id,
+        &address,
+        Some(1),
+        nobody.id,
+        VoteStatus::Confirmed,
+        Screened::default(),
+    )
+    .await
+    .unwrap();
+    assert!(voter.shadow);
 }
@@ -0,0 +1,28 @@
+use threads_crush::{
+    common::settings::RateLimitRule,
+    rate_limit::{memory::MemoryRateLimitStore, RateLimitStore},
+};
+
+fn rule() -> RateLimitRule {
+    RateLimitRule {
+        path: "/api/vote".to_string(),
+        method: Some("POST".to_string()),
+        burst: 2,
+        per_minute: 1,
+    }
+}
+
+#[tokio::test]
+async fn limits_after_burst() {
+    let store = MemoryRateLimitStore::default();
+    let rule = rule();
+
+    assert!(store.take("a", &rule).await.unwrap().is_none());
+    assert!(store.take("a", &rule).await.unwrap().is_none());
+
+    let wait = store.take("a", &rule).await.unwrap().unwrap();
+    assert!(wait.as_secs() > 0 && wait.as_secs() <= 60);
+
+    // other clients have their own bucket
+    assert!(store.take("b", &rule).await.unwrap().is_none());
+}
@@ -0,0 +1,105 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use threads_crush::{
+    common::settings::RiskSettings,
+    risk::{assess, is_scripted, RiskLevel, RiskSignals},
+};
+
+fn browser() -> RiskSignals {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::USER_AGENT,
+        HeaderValue::from_static("Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X)"),
+    );
+    headers.insert(header::ACCEPT_LANGUAGE, HeaderValue::from_static("en-US"));
+    headers.insert(
+        header::ORIGIN,
+        HeaderValue::from_static("https://threadscrush.com"),
+    );
+
+    RiskSignals::from_headers(&headers)
+}
+
+#[test]
+fn lets_browsers_through() {
+    let settings = RiskSettings::default();
+
+    let assessment = assess(
+        &settings,
+        &RiskSignals {
+            captcha_score: Some(0.9),
+            ..browser()
+        },
+    );
+
+    assert_eq!(assessment.level, RiskLevel::Low);
+    assert!(assessment.score < settings.challenge_score);
+}
+
+#[test]
+fn challenges_low_captcha_scores() {
+    let settings = RiskSettings::default();
+
+    let assessment = assess(
+        &settings,
+        &RiskSignals {
+            captcha_score: Some(0.1),
+            address_votes: 1,
+            ..browser()
+        },
+    );
+
+    assert_eq!(assessment.level, RiskLevel::Medium);
+    assert_eq!(assessment.reasons, vec!["low captcha score"]);
+}
+
+#[test]
+fn flags_scripts_from_busy_addresses() {
+    let settings = RiskSettings::default();
+    let mut headers = HeaderMap::new();
+    headers.insert(header::USER_AGENT, HeaderValue::from_static("curl/8.4.0"));
+
+    let assessment = assess(
+        &settings,
+        &RiskSignals {
+            address_votes: 5,
+            network_votes: 5,
+            ..RiskSignals::from_headers(&headers)
+        },
+    );
+
+    assert_eq!(assessment.level, RiskLevel::High);
+    assert_eq!(
+        assessment.reasons,
+        vec![
+            "scripted user agent",
+            "no accept-language",
+            "no origin",
+            "busy address"
+        ]
+    );
+    assert!(assessment.score <= 1.0);
+}
+
+#[test]
+fn tells_scripts_from_browsers() {
+    for user_agent in [
+        "curl/8.4.0",
+        "python-requests/2.31.0",
+        "Go-http-client/1.1",
+        "Java/17.0.2",
+        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 HeadlessChrome/120.0.0.0",
+        "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+        "Mozilla/5.0 (compatible; Baiduspider/2.0)",
+    ] {
+        assert!(is_scripted(user_agent), "{}", user_agent);
+    }
+
+    for user_agent in [
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X)",
+        "Mozilla/5.0 (Linux; Android 12; CUBOT_X30) AppleWebKit/537.36 Chrome/120.0.0.0",
+        "Mozilla/5.0 (Linux; Android 13; Pixel 7 Build/TQ3A.230805.001; wv) Chrome/120.0 Instagram 312.0",
+        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 Safari/605.1.15",
+    ] {
+        assert!(!is_scripted(user_agent), "{}", user_agent);
+    }
+}
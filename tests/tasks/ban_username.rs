@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use loco_rs::{prelude::*, testing};
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::_entities::{user, voter},
+    tasks::ban_username::BanUsername,
+};
+
+#[tokio::test]
+#[serial]
+async fn can_ban_username() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let vars = BTreeMap::from([("username".to_string(), "Zuck".to_string())]);
+    BanUsername.run(&boot.app_context, &vars).await.unwrap();
+
+    assert!(user::Model::find_by_username(&boot.app_context.db, "zuck")
+        .await
+        .is_err());
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_ban_without_username() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    assert!(BanUsername
+        .run(&boot.app_context, &BTreeMap::new())
+        .await
+        .is_err());
+}
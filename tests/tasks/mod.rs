@@ -0,0 +1,4 @@
+mod ban_username;
+mod prune_orphans;
+mod purge_voters;
+mod stats;
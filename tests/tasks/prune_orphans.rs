@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use loco_rs::{prelude::*, testing};
+use serial_test::serial;
+use threads_crush::{app::App, models::_entities::user, tasks::prune_orphans::PruneOrphans};
+
+#[tokio::test]
+#[serial]
+async fn can_prune_orphans() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    PruneOrphans
+        .run(&boot.app_context, &BTreeMap::new())
+        .await
+        .unwrap();
+
+    assert!(
+        user::Model::find_by_username(&boot.app_context.db, "nobody")
+            .await
+            .is_err()
+    );
+    for username in ["zuck", "mosseri", "threadscrush"] {
+        assert!(
+            user::Model::find_by_username(&boot.app_context.db, username)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use loco_rs::{prelude::*, testing};
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serial_test::serial;
+use threads_crush::{app::App, models::_entities::voter, tasks::purge_voters::PurgeVoters};
+
+fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_purge_by_cidr() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    PurgeVoters
+        .run(&boot.app_context, &vars(&[("cidr", "10.0.0.0/16")]))
+        .await
+        .unwrap();
+
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_purge_a_single_network() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    PurgeVoters
+        .run(&boot.app_context, &vars(&[("cidr", "10.0.1.7/24")]))
+        .await
+        .unwrap();
+
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 4);
+}
+
+#[tokio::test]
+#[serial]
+async fn can_purge_before_a_date() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    PurgeVoters
+        .run(&boot.app_context, &vars(&[("before", "1970-01-02")]))
+        .await
+        .unwrap();
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 6);
+
+    PurgeVoters
+        .run(
+            &boot.app_context,
+            &vars(&[("before", "9999-01-01"), ("cidr", "2001:db8::/40")]),
+        )
+        .await
+        .unwrap();
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 5);
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_purge_without_a_filter() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    assert!(PurgeVoters
+        .run(&boot.app_context, &vars(&[]))
+        .await
+        .is_err());
+    assert!(PurgeVoters
+        .run(&boot.app_context, &vars(&[("cidr", "10.0.0.0/25")]))
+        .await
+        .is_err());
+    assert!(PurgeVoters
+        .run(&boot.app_context, &vars(&[("before", "yesterday")]))
+        .await
+        .is_err());
+
+    let left = voter::Entity::find()
+        .count(&boot.app_context.db)
+        .await
+        .unwrap();
+    assert_eq!(left, 6);
+}
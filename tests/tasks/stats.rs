@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+use loco_rs::{prelude::*, testing};
+use serial_test::serial;
+use threads_crush::{app::App, tasks::stats::Stats};
+
+#[tokio::test]
+#[serial]
+async fn can_print_stats() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let vars = BTreeMap::from([("top".to_string(), "2".to_string())]);
+    assert!(Stats.run(&boot.app_context, &vars).await.is_ok());
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_print_stats_with_invalid_top() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let vars = BTreeMap::from([("top".to_string(), "-1".to_string())]);
+    assert!(Stats.run(&boot.app_context, &vars).await.is_err());
+}
@@ -0,0 +1,103 @@
+use threads_crush::{
+    common::settings::{AddressHashSettings, Settings},
+    utils::address_hash::{AddressHasher, NetworkFilterError},
+};
+
+fn hasher(secret: &str, previous_secret: Option<&str>) -> AddressHasher {
+    AddressHasher::from_settings(&Settings {
+        address_hash: AddressHashSettings {
+            secret: Some(secret.to_string()),
+            previous_secret: previous_secret.map(ToString::to_string),
+        },
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+/// The stored network of an address in the network
+fn network(hasher: &AddressHasher, address: &str) -> String {
+    hasher.hash(address).network.unwrap()
+}
+
+#[test]
+fn hashes_the_stored_network() {
+    let hasher = hasher("test-address-secret", Some("old-address-secret"));
+    let address = hasher.hash("10.0.0.1");
+
+    assert_ne!(address.network.as_deref(), Some("10.0.0.0/24"));
+    assert_eq!(address.network, hasher.hash("10.0.0.200").network);
+    assert_ne!(address.network, hasher.hash("10.0.1.1").network);
+    assert_eq!(
+        address.previous_network,
+        self::hasher("old-address-secret", None)
+            .hash("10.0.0.1")
+            .network
+    );
+}
+
+#[test]
+fn expands_cidr_to_stored_networks() {
+    let hasher = hasher("test-address-secret", None);
+
+    assert_eq!(
+        hasher
+            .stored_networks("10.0.0.0/24".parse().unwrap())
+            .unwrap(),
+        vec![network(&hasher, "10.0.0.1")]
+    );
+    assert_eq!(
+        hasher
+            .stored_networks("10.0.1.7/23".parse().unwrap())
+            .unwrap(),
+        vec![network(&hasher, "10.0.0.1"), network(&hasher, "10.0.1.1")]
+    );
+    assert_eq!(
+        hasher
+            .stored_networks("10.0.0.0/16".parse().unwrap())
+            .unwrap()
+            .len(),
+        256
+    );
+    assert_eq!(
+        hasher
+            .stored_networks("2001:db8::/46".parse().unwrap())
+            .unwrap(),
+        vec![
+            network(&hasher, "2001:db8::1"),
+            network(&hasher, "2001:db8:1::1"),
+            network(&hasher, "2001:db8:2::1"),
+            network(&hasher, "2001:db8:3::1")
+        ]
+    );
+}
+
+#[test]
+fn expands_cidr_with_previous_secret() {
+    let hasher = hasher("test-address-secret", Some("old-address-secret"));
+    let address = hasher.hash("10.0.0.1");
+
+    assert_eq!(
+        hasher
+            .stored_networks("10.0.0.0/24".parse().unwrap())
+            .unwrap(),
+        vec![address.network.unwrap(), address.previous_network.unwrap()]
+    );
+}
+
+#[test]
+fn rejects_unanswerable_cidrs() {
+    let hasher = hasher("test-address-secret", None);
+
+    assert!(matches!(
+        hasher.stored_networks("10.0.0.0/28".parse().unwrap()),
+        Err(NetworkFilterError::TooNarrow(24))
+    ));
+    assert!(matches!(
+        hasher.stored_networks("2001:db8::/64".parse().unwrap()),
+        Err(NetworkFilterError::TooNarrow(48))
+    ));
+    assert!(matches!(
+        hasher.stored_networks("10.0.0.0/8".parse().unwrap()),
+        Err(NetworkFilterError::TooWide(4096))
+    ));
+}
@@ -0,0 +1,69 @@
+use std::net::IpAddr;
+
+use axum::http::{HeaderMap, HeaderValue};
+use threads_crush::{
+    common::settings::{ClientIpHeader, ClientIpSettings},
+    utils::get_ip::ClientIpResolver,
+};
+
+fn resolver(headers: Vec<ClientIpHeader>) -> ClientIpResolver {
+    ClientIpResolver::new(&ClientIpSettings {
+        trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+        headers,
+    })
+}
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+#[test]
+fn ignores_headers_from_untrusted_peers() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static("1.1.1.1"));
+
+    let resolved =
+        resolver(vec![ClientIpHeader::XForwardedFor]).resolve(ip("203.0.113.7"), &headers);
+
+    assert_eq!(resolved, ip("203.0.113.7"));
+}
+
+#[test]
+fn skips_trusted_hops_in_forwarded_for() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-forwarded-for",
+        HeaderValue::from_static("6.6.6.6, 198.51.100.1, 10.0.0.2"),
+    );
+
+    let resolved = resolver(vec![ClientIpHeader::XForwardedFor]).resolve(ip("10.0.0.1"), &headers);
+
+    assert_eq!(resolved, ip("198.51.100.1"));
+}
+
+#[test]
+fn reads_forwarded_header() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "forwarded",
+        HeaderValue::from_static("for=\"[2001:db8::1]:4711\";proto=https"),
+    );
+
+    let resolved = resolver(vec![ClientIpHeader::Forwarded]).resolve(ip("10.0.0.1"), &headers);
+
+    assert_eq!(resolved, ip("2001:db8::1"));
+}
+
+#[test]
+fn only_honors_configured_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert("cf-connecting-ip", HeaderValue::from_static("6.6.6.6"));
+    headers.insert(
+        "x-envoy-external-address",
+        HeaderValue::from_static("198.51.100.1"),
+    );
+
+    let resolved = resolver(vec![ClientIpHeader::Envoy]).resolve(ip("10.0.0.1"), &headers);
+
+    assert_eq!(resolved, ip("198.51.100.1"));
+}
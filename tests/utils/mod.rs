@@ -0,0 +1,3 @@
+mod address_hash;
+mod get_ip;
+mod voter_resolver;
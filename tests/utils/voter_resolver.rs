@@ -0,0 +1,120 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use threads_crush::{
+    common::settings::{CompositeCap, VoterIdentityMode, VoterIdentitySettings},
+    utils::voter_resolver::VoterIdentityResolver,
+};
+
+fn resolver(mode: VoterIdentityMode) -> VoterIdentityResolver {
+    VoterIdentityResolver::from_settings(&VoterIdentitySettings {
+        mode,
+        cookie_secret: Some("test-cookie-secret".to_string()),
+        per_ip_cap: 3,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn collapses_ipv6_to_prefix() {
+    let resolved = resolver(VoterIdentityMode::IpPrefix).resolve(
+        "2001:db8::1:2:3:4",
+        &HeaderMap::new(),
+        false,
+    );
+
+    assert_eq!(resolved.key.as_deref(), Some("2001:db8::/64"));
+    assert_eq!(resolved.cap, None);
+}
+
+#[test]
+fn accepts_issued_cookie() {
+    let resolver = resolver(VoterIdentityMode::Cookie);
+
+    let issued = resolver.resolve("203.0.113.7", &HeaderMap::new(), true);
+    let cookie = issued.set_cookie.unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::COOKIE,
+        HeaderValue::from_str(cookie.split(';').next().unwrap()).unwrap(),
+    );
+
+    let resolved = resolver.resolve("198.51.100.1", &headers, true);
+
+    assert_eq!(resolved.key, issued.key);
+    assert_eq!(resolved.set_cookie, None);
+    assert_eq!(resolved.cap_address, "198.51.100.1");
+    assert_eq!(resolved.cap, Some(3));
+}
+
+#[test]
+fn rejects_forged_cookie() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::COOKIE,
+        HeaderValue::from_static("crush_voter=someone.00ff"),
+    );
+
+    let resolved = resolver(VoterIdentityMode::Composite).resolve("203.0.113.7", &headers, false);
+
+    assert_eq!(resolved.key, None);
+}
+
+#[test]
+fn needs_cookie_secret() {
+    let result = VoterIdentityResolver::from_settings(&VoterIdentitySettings {
+        mode: VoterIdentityMode::Cookie,
+        cookie_secret: None,
+        per_ip_cap: 3,
+        ..Default::default()
+    });
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn caps_composite_per_configured_address() {
+    let resolver = |composite_cap| {
+        VoterIdentityResolver::from_settings(&VoterIdentitySettings {
+            mode: VoterIdentityMode::Composite,
+            cookie_secret: Some("test-cookie-secret".to_string()),
+            composite_cap,
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let resolved =
+        resolver(CompositeCap::IpPrefix).resolve("2001:db8::1:2:3:4", &HeaderMap::new(), true);
+    assert_eq!(resolved.cap_address, "2001:db8::/64");
+
+    let resolved =
+        resolver(CompositeCap::Network).resolve("2001:db8::1:2:3:4", &HeaderMap::new(), true);
+    assert_eq!(resolved.cap_address, "2001:db8::/48");
+    let resolved = resolver(CompositeCap::Network).resolve("10.0.0.7", &HeaderMap::new(), true);
+    assert_eq!(resolved.cap_address, "10.0.0.0/24");
+}
+
+#[test]
+fn can_issue_cookie_without_secure() {
+    let resolver = |cookie_secure| {
+        VoterIdentityResolver::from_settings(&VoterIdentitySettings {
+            mode: VoterIdentityMode::Cookie,
+            cookie_secret: Some("test-cookie-secret".to_string()),
+            cookie_secure,
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let cookie = resolver(true)
+        .resolve("203.0.113.7", &HeaderMap::new(), true)
+        .set_cookie
+        .unwrap();
+    assert!(cookie.contains("; Secure;"));
+
+    let cookie = resolver(false)
+        .resolve("203.0.113.7", &HeaderMap::new(), true)
+        .set_cookie
+        .unwrap();
+    assert!(!cookie.contains("Secure"));
+}
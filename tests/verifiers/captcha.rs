@@ -0,0 +1,63 @@
+use threads_crush::{
+    common::settings::{CaptchaProvider, CaptchaSettings},
+    verifiers::captcha::{self, CaptchaError},
+};
+
+#[tokio::test]
+async fn fake_accepts_any_token() {
+    let verifier = captcha::from_settings(&CaptchaSettings {
+        provider: CaptchaProvider::Fake,
+        fake_score: Some(0.3),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let verification = verifier.verify("anything", Some("10.0.0.1")).await.unwrap();
+    assert_eq!(verification.score, Some(0.3));
+
+    assert!(matches!(
+        verifier.verify("", None).await,
+        Err(CaptchaError::Failed)
+    ));
+}
+
+#[tokio::test]
+async fn fake_checks_configured_token() {
+    let verifier = captcha::from_settings(&CaptchaSettings {
+        provider: CaptchaProvider::Fake,
+        fake_token: Some("pass".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(verifier.verify("pass", None).await.is_ok());
+    assert!(matches!(
+        verifier.verify("fail", None).await,
+        Err(CaptchaError::Failed)
+    ));
+}
+
+#[test]
+fn providers_need_secret() {
+    for provider in [
+        CaptchaProvider::Recaptcha,
+        CaptchaProvider::Hcaptcha,
+        CaptchaProvider::Turnstile,
+    ] {
+        assert!(matches!(
+            captcha::from_settings(&CaptchaSettings {
+                provider,
+                secret: Some(String::new()),
+                ..Default::default()
+            }),
+            Err(CaptchaError::MissingSecret)
+        ));
+
+        assert!(captcha::from_settings(&CaptchaSettings {
+            provider,
+            secret: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+}
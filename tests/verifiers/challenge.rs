@@ -0,0 +1,83 @@
+use sea_orm::DatabaseConnection;
+use threads_crush::{
+    common::settings::{
+        CaptchaProvider, CaptchaSettings, IpListPolicy, IpListSettings, RiskSettings, Settings,
+    },
+    verifiers::Verifiers,
+};
+
+fn settings(risk: RiskSettings) -> Settings {
+    Settings {
+        captcha: CaptchaSettings {
+            provider: CaptchaProvider::Fake,
+            ..Default::default()
+        },
+        risk,
+        ..Default::default()
+    }
+}
+
+fn fake_challenge() -> CaptchaSettings {
+    CaptchaSettings {
+        provider: CaptchaProvider::Fake,
+        fake_token: Some("challenge".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn risk_scoring_needs_challenge() {
+    let db = DatabaseConnection::default();
+
+    let result = Verifiers::from_settings(
+        &settings(RiskSettings {
+            enable: true,
+            ..Default::default()
+        }),
+        &db,
+    );
+    assert!(result.is_err());
+
+    let verifiers = Verifiers::from_settings(
+        &settings(RiskSettings {
+            enable: true,
+            challenge: Some(fake_challenge()),
+            ..Default::default()
+        }),
+        &db,
+    )
+    .unwrap();
+    assert!(verifiers.challenge.is_some());
+
+    let verifiers = Verifiers::from_settings(&settings(RiskSettings::default()), &db).unwrap();
+    assert!(verifiers.challenge.is_none());
+}
+
+#[test]
+fn challenge_list_needs_challenge() {
+    let mut settings = settings(RiskSettings::default());
+    settings.ip_reputation.lists.push(IpListSettings {
+        name: "tor".to_string(),
+        path: "config/ip_lists/tor_exits.txt".to_string(),
+        policy: IpListPolicy::Challenge,
+    });
+
+    assert!(Verifiers::from_settings(&settings, &DatabaseConnection::default()).is_err());
+}
+
+#[test]
+fn challenge_differs_from_captcha() {
+    let result = Verifiers::from_settings(
+        &settings(RiskSettings {
+            enable: true,
+            challenge: Some(CaptchaSettings {
+                provider: CaptchaProvider::Fake,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        &DatabaseConnection::default(),
+    );
+
+    assert!(result.is_err());
+}
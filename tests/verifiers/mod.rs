@@ -0,0 +1,4 @@
+mod captcha;
+mod challenge;
+mod username;
+mod username_cache;
@@ -0,0 +1,75 @@
+use threads_crush::{
+    common::settings::{UsernameVerifierBackend, UsernameVerifierSettings},
+    verifiers::username::{self, profile_bio, UsernameVerifierError},
+};
+
+#[tokio::test]
+async fn allowlist_accepts_listed_usernames() {
+    let verifier = username::from_settings(&UsernameVerifierSettings {
+        backend: UsernameVerifierBackend::Allowlist,
+        allowlist: vec!["Zuck".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert!(!verifier.exists("mosseri").await.unwrap());
+    assert_eq!(
+        verifier.fetch_profile("zuck").await.unwrap(),
+        Some(String::new())
+    );
+    assert_eq!(verifier.fetch_profile("mosseri").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn empty_allowlist_accepts_everyone() {
+    let verifier = username::from_settings(&UsernameVerifierSettings {
+        backend: UsernameVerifierBackend::Allowlist,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(verifier.exists("anyone").await.unwrap());
+}
+
+#[tokio::test]
+async fn fixture_serves_profiles() {
+    let verifier = username::from_settings(&UsernameVerifierSettings {
+        backend: UsernameVerifierBackend::Fixture,
+        fixture_path: Some("tests/fixtures/threads_profiles.yaml".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert!(verifier.exists("threadscrush").await.unwrap());
+    assert!(!verifier.exists("nobody").await.unwrap());
+    assert_eq!(
+        verifier.fetch_profile("mosseri").await.unwrap().as_deref(),
+        Some("Head of Instagram and Threads")
+    );
+}
+
+#[test]
+fn fixture_needs_path() {
+    assert!(matches!(
+        username::from_settings(&UsernameVerifierSettings {
+            backend: UsernameVerifierBackend::Fixture,
+            ..Default::default()
+        }),
+        Err(UsernameVerifierError::MissingFixturePath)
+    ));
+}
+
+#[test]
+fn reads_bio_from_profile_page() {
+    let page = r#"<html><head>
+<meta content="Mostly superintelligence now &amp; crush-1a2b3c4d" property="og:description" />
+</head><body>reply from @someone: crush-ffffffff</body></html>"#;
+
+    assert_eq!(
+        profile_bio(page).as_deref(),
+        Some("Mostly superintelligence now & crush-1a2b3c4d")
+    );
+    assert_eq!(profile_bio("<body>crush-ffffffff</body>"), None);
+}
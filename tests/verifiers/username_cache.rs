@@ -0,0 +1,117 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use loco_rs::testing;
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    common::settings::UsernameCacheSettings,
+    verifiers::{
+        username::{UsernameVerifier, UsernameVerifierError},
+        username_cache::CachedUsernameVerifier,
+    },
+};
+
+/// Counts the lookups that reach it, every username but `down` exists
+#[derive(Default)]
+struct CountingVerifier {
+    lookups: AtomicUsize,
+}
+
+#[async_trait]
+impl UsernameVerifier for CountingVerifier {
+    async fn exists(&self, username: &str) -> Result<bool, UsernameVerifierError> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        if username == "down" {
+            return Err(UsernameVerifierError::LookupFailed);
+        }
+        Ok(true)
+    }
+
+    async fn fetch_profile(
+        &self,
+        _username: &str,
+    ) -> Result<Option<String>, UsernameVerifierError> {
+        Ok(None)
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn reuses_fresh_results() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let inner = Arc::new(CountingVerifier::default());
+    let verifier = CachedUsernameVerifier::new(
+        inner.clone(),
+        boot.app_context.db.clone(),
+        &UsernameCacheSettings {
+            enable: true,
+            ttl: 60,
+            error_ttl: 60,
+        },
+    );
+
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert_eq!(inner.lookups.load(Ordering::SeqCst), 1);
+
+    // failures are cached too, so threads isn't hammered while it is down
+    assert!(matches!(
+        verifier.exists("down").await,
+        Err(UsernameVerifierError::LookupFailed)
+    ));
+    assert!(verifier.exists("down").await.is_err());
+    assert_eq!(inner.lookups.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn looks_up_expired_results_again() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let inner = Arc::new(CountingVerifier::default());
+    let verifier = CachedUsernameVerifier::new(
+        inner.clone(),
+        boot.app_context.db.clone(),
+        &UsernameCacheSettings {
+            enable: true,
+            ttl: 0,
+            error_ttl: 0,
+        },
+    );
+
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert!(verifier.exists("zuck").await.unwrap());
+    assert_eq!(inner.lookups.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn shares_concurrent_lookups() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let inner = Arc::new(CountingVerifier::default());
+    let verifier = CachedUsernameVerifier::new(
+        inner.clone(),
+        boot.app_context.db.clone(),
+        &UsernameCacheSettings {
+            enable: true,
+            ttl: 60,
+            error_ttl: 60,
+        },
+    );
+
+    let (a, b, c) = tokio::join!(
+        verifier.exists("mosseri"),
+        verifier.exists("mosseri"),
+        verifier.exists("mosseri"),
+    );
+    assert!(a.unwrap() && b.unwrap() && c.unwrap());
+    assert_eq!(inner.lookups.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1 @@
+mod verify_vote;
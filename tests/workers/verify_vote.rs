@@ -0,0 +1,119 @@
+use loco_rs::{prelude::*, testing, worker::Worker};
+use sea_orm::EntityTrait;
+use serial_test::serial;
+use threads_crush::{
+    app::App,
+    models::{
+        _entities::{season, user, voter},
+        voter::{Screened, VoteStatus, VoterKey},
+    },
+    utils::address_hash::AddressHasher,
+    workers::verify_vote::{VerifyVoteWorker, VerifyVoteWorkerArgs},
+};
+
+/// Casts a pending vote for `username` from a fresh address
+async fn pending_vote(ctx: &AppContext, username: &str, ip: &str) -> voter::Model {
+    let season = season::Model::find_active(&ctx.db).await.unwrap().unwrap();
+    let address = AddressHasher::from_context(ctx).unwrap().hash(ip);
+    let voted_user = user::Model::add(&ctx.db, username).await.unwrap();
+
+    voter::Model::add(
+        &ctx.db,
+        VoterKey::Address(&address.hash),
+        season.id,
+        &address,
+        None,
+        voted_user.id,
+        VoteStatus::Pending,
+        Screened::default(),
+    )
+    .await
+    .unwrap()
+}
+
+async fn verify(ctx: &AppContext, voter: &voter::Model) {
+    VerifyVoteWorker::build(ctx)
+        .perform(VerifyVoteWorkerArgs { voter_id: voter.id })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn confirms_existing_username() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let voter = pending_vote(&boot.app_context, "mosseri", "192.0.2.1").await;
+    verify(&boot.app_context, &voter).await;
+
+    let voter = voter::Entity::find_by_id(voter.id)
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(voter.status(), VoteStatus::Confirmed);
+}
+
+#[tokio::test]
+#[serial]
+async fn drops_unknown_username() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let voter = pending_vote(&boot.app_context, "madeup", "192.0.2.1").await;
+    verify(&boot.app_context, &voter).await;
+
+    assert!(voter::Entity::find_by_id(voter.id)
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(
+        user::Model::find_by_username(&boot.app_context.db, "madeup")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn keeps_unknown_username_with_other_votes() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let first = pending_vote(&boot.app_context, "madeup", "192.0.2.1").await;
+    let second = pending_vote(&boot.app_context, "madeup", "192.0.2.2").await;
+    verify(&boot.app_context, &second).await;
+
+    assert!(voter::Entity::find_by_id(first.id)
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .is_some());
+    assert!(
+        user::Model::find_by_username(&boot.app_context.db, "madeup")
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn skips_verified_votes() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let voter = pending_vote(&boot.app_context, "madeup", "192.0.2.1").await;
+    voter::Model::set_status(&boot.app_context.db, voter.id, VoteStatus::Failed)
+        .await
+        .unwrap();
+    verify(&boot.app_context, &voter).await;
+
+    let voter = voter::Entity::find_by_id(voter.id)
+        .one(&boot.app_context.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(voter.status(), VoteStatus::Failed);
+}